@@ -0,0 +1,173 @@
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::ContentType, StatusCode},
+    web::Bytes,
+    HttpMessage, HttpResponse,
+};
+
+use crate::{
+    verify_config::{error_code, rejection_body, rejection_status},
+    Error, VerifyConfig,
+};
+
+type ErrorHandler = Arc<dyn Fn(Error) -> HttpResponse + Send + Sync>;
+
+/// Actix-web middleware verifying the `Tl-Signature` of inbound requests.
+///
+/// The request payload is buffered & verified along with the method, path & all headers.
+/// Verified requests are passed to the next service with the payload re-injected &
+/// the [`VerifiedSignature`](crate::VerifiedSignature) inserted into the request extensions.
+/// Requests failing verification are rejected, by default with `401 Unauthorized` &
+/// a json body describing the failure, see [`ActixVerifySignature::error_handler`].
+///
+/// The payload size is limited by the [`PayloadConfig`](actix_web::web::PayloadConfig).
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let public_key: &[u8] = unimplemented!();
+/// use actix_web::{web, App};
+///
+/// let keys = truelayer_signing::VerifyingKeySet::new()
+///     .with_pem("45fc75cf-5649-4134-84b3-192c2c78e990", public_key)?;
+/// let config = truelayer_signing::VerifyConfig::new(keys).require_header("Idempotency-Key");
+///
+/// let verify_signature = truelayer_signing::ActixVerifySignature::new(config);
+///
+/// let app = App::new().service(
+///     web::resource("/payouts")
+///         .wrap(verify_signature)
+///         .route(web::post().to(|body: web::Bytes| async move { body })),
+/// );
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct ActixVerifySignature {
+    verify: VerifyConfig,
+    error_handler: ErrorHandler,
+}
+
+/// Debug does not display key info.
+impl fmt::Debug for ActixVerifySignature {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ActixVerifySignature")
+            .field("verify", &self.verify)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ActixVerifySignature {
+    /// Verify requests using the given keys & policy.
+    pub fn new(verify: VerifyConfig) -> Self {
+        Self {
+            verify,
+            error_handler: Arc::new(reject),
+        }
+    }
+
+    /// Sets the response for requests failing verification.
+    ///
+    /// Default `401 Unauthorized`, or `502 Bad Gateway` if the jwks could not be fetched,
+    /// or `503 Service Unavailable` if the replay guard failed,
+    /// with a json body describing the failure with a stable `error` code, e.g.
+    /// `{"error":"invalid_signature","reason":"signature verification failed"}`.
+    pub fn error_handler(
+        mut self,
+        handler: impl Fn(Error) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        self.error_handler = Arc::new(handler);
+        self
+    }
+}
+
+fn reject(err: Error) -> HttpResponse {
+    let status = StatusCode::from_u16(rejection_status(&err)).unwrap_or(StatusCode::UNAUTHORIZED);
+    HttpResponse::build(status)
+        .content_type(ContentType::json())
+        .body(rejection_body(error_code(&err), &err.reason()))
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ActixVerifySignature
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = ActixVerifySignatureService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ActixVerifySignatureService {
+            service: Rc::new(service),
+            config: self.clone(),
+        }))
+    }
+}
+
+/// Service produced by [`ActixVerifySignature`].
+pub struct ActixVerifySignatureService<S> {
+    service: Rc<S>,
+    config: ActixVerifySignature,
+}
+
+impl<S> fmt::Debug for ActixVerifySignatureService<S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ActixVerifySignatureService")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for ActixVerifySignatureService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut request: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let body = request.extract::<Bytes>().await?;
+
+            let headers = request
+                .headers()
+                .iter()
+                .map(|(h, v)| (h.as_str(), v.as_bytes()));
+            let verified = config
+                .verify
+                .verify(request.method().as_str(), request.path(), headers, &body)
+                .await;
+            match verified {
+                Ok(verified) => request.extensions_mut().insert(verified),
+                Err(err) => {
+                    let response = (config.error_handler)(err);
+                    return Ok(request.into_response(response).map_into_right_body());
+                }
+            };
+
+            request.set_payload(Payload::from(body));
+            service
+                .call(request)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
@@ -0,0 +1,70 @@
+use ::axum::{
+    body::Bytes,
+    extract::{FromRef, FromRequest, Request},
+    response::{IntoResponse, Response},
+};
+
+use crate::{tower::verify_rejection, VerifyConfig};
+
+/// Axum extractor verifying the request `Tl-Signature` using the
+/// [`VerifyConfig`] from the router state.
+///
+/// Once verified the body is extracted as `T`, e.g. [`Bytes`], `String` or
+/// [`Json<_>`](::axum::Json). Requests failing verification are rejected with
+/// `401 Unauthorized` & a json body describing the failure.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let jwks: &[u8] = unimplemented!();
+/// use axum::{routing::post, Json, Router};
+/// use truelayer_signing::{VerifiedBody, VerifyConfig, VerifyingKeySet};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Webhook {
+///     event_type: String,
+/// }
+///
+/// async fn receive_hook(VerifiedBody(Json(hook)): VerifiedBody<Json<Webhook>>) {
+///     // handle verified hook
+/// }
+///
+/// let config = VerifyConfig::new(VerifyingKeySet::from_jwks(jwks)?)
+///     .require_header("X-Tl-Webhook-Timestamp");
+///
+/// let app: Router = Router::new()
+///     .route("/hook", post(receive_hook))
+///     .with_state(config);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerifiedBody<T = Bytes>(pub T);
+
+impl<S, T> FromRequest<S> for VerifiedBody<T>
+where
+    S: Send + Sync,
+    VerifyConfig: FromRef<S>,
+    T: FromRequest<S>,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let config = VerifyConfig::from_ref(state);
+        let (parts, body) = request.into_parts();
+
+        // buffer the body, respecting any `DefaultBodyLimit`
+        let body = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        if let Err(err) = crate::verify_request_parts(&parts, &body, &config).await {
+            return Err(verify_rejection(&err));
+        }
+
+        let request = Request::from_parts(parts, body.into());
+        T::from_request(request, state)
+            .await
+            .map(VerifiedBody)
+            .map_err(IntoResponse::into_response)
+    }
+}
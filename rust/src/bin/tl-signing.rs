@@ -0,0 +1,377 @@
+//! `tl-signing` command line tool to sign, verify & inspect `Tl-Signature` header values.
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use serde_json::json;
+use truelayer_signing::{DiagnosticBody, Method, SignerBuilder, TrailingSlash};
+
+/// Sign, verify & inspect TrueLayer `Tl-Signature` header values.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Print machine-readable json output.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Produce a `Tl-Signature` header value for a request.
+    Sign(SignArgs),
+    /// Verify a `Tl-Signature` header value for a request.
+    ///
+    /// Exits with status 1 if verification fails.
+    Verify(VerifyArgs),
+    /// Decode a `Tl-Signature` JOSE header & optionally print the signing payload for a request.
+    Inspect(InspectArgs),
+}
+
+#[derive(Args)]
+struct SignArgs {
+    /// Signing key id.
+    #[arg(long)]
+    kid: String,
+    /// P-521 private key pem file.
+    #[arg(long)]
+    key: PathBuf,
+    /// `jku` to include in the signature.
+    #[arg(long)]
+    jku: Option<String>,
+    #[command(flatten)]
+    request: RequestArgs,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct PublicKeyArgs {
+    /// Public key pem file.
+    #[arg(long)]
+    pem: Option<PathBuf>,
+    /// JWKs json file.
+    #[arg(long)]
+    jwks: Option<PathBuf>,
+}
+
+enum PublicKeyFile {
+    Pem(Vec<u8>),
+    Jwks(Vec<u8>),
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    #[command(flatten)]
+    public_key: PublicKeyArgs,
+    /// `Tl-Signature` header value.
+    #[arg(long)]
+    signature: String,
+    /// Header name that must be included in the signature, may be repeated.
+    #[arg(long)]
+    require_header: Vec<String>,
+    /// Reject signatures of the path with a trailing slash added or removed.
+    #[arg(long)]
+    strict_trailing_slash: bool,
+    #[command(flatten)]
+    request: RequestArgs,
+}
+
+#[derive(Args)]
+struct InspectArgs {
+    /// `Tl-Signature` header value.
+    signature: String,
+    /// Request method, e.g. POST. Prints the signing payload along with --path.
+    #[arg(long, value_parser = parse_method, requires = "path")]
+    method: Option<Method>,
+    /// Request path, e.g. /payouts.
+    #[arg(long, value_parser = parse_path, requires = "method")]
+    path: Option<String>,
+    /// Request header "Name: value", may be repeated.
+    #[arg(long = "header", short = 'H', value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+    /// Request body file, `-` to read stdin. Default empty.
+    #[arg(long)]
+    body: Option<PathBuf>,
+}
+
+impl InspectArgs {
+    fn request(&self) -> Option<RequestArgs> {
+        Some(RequestArgs {
+            method: self.method?,
+            path: self.path.clone()?,
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+        })
+    }
+}
+
+#[derive(Args)]
+struct RequestArgs {
+    /// Request method, e.g. POST.
+    #[arg(long, value_parser = parse_method)]
+    method: Method,
+    /// Request path, e.g. /payouts.
+    #[arg(long, value_parser = parse_path)]
+    path: String,
+    /// Request header "Name: value", may be repeated.
+    #[arg(long = "header", short = 'H', value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+    /// Request body file, `-` to read stdin. Default empty.
+    #[arg(long)]
+    body: Option<PathBuf>,
+}
+
+impl RequestArgs {
+    fn headers(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes()))
+    }
+
+    fn read_body(&self) -> Result<Vec<u8>, String> {
+        match &self.body {
+            None => Ok(vec![]),
+            Some(path) if path.as_os_str() == "-" => {
+                let mut body = vec![];
+                io::stdin()
+                    .read_to_end(&mut body)
+                    .map_err(|e| format!("reading stdin: {e}"))?;
+                Ok(body)
+            }
+            Some(path) => read(path),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // report invalid arguments as json, if requested
+        Err(err) if err.use_stderr() && std::env::args().any(|arg| arg == "--json") => {
+            let err = err.to_string();
+            let err = err.lines().next().unwrap_or_default();
+            println!("{}", json!({ "error": err.trim_start_matches("error: ") }));
+            return ExitCode::from(2);
+        }
+        Err(err) => err.exit(),
+    };
+    let result = match &cli.command {
+        Command::Sign(args) => sign(args),
+        Command::Verify(args) => verify(args),
+        Command::Inspect(args) => inspect(args),
+    };
+
+    match result {
+        Ok(output) => {
+            output.print(cli.json);
+            match output.success {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            }
+        }
+        Err(err) => {
+            match cli.json {
+                true => println!("{}", json!({ "error": err })),
+                false => eprintln!("error: {err}"),
+            }
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Command output.
+struct Output {
+    success: bool,
+    text: String,
+    json: serde_json::Value,
+}
+
+impl Output {
+    fn print(&self, json: bool) {
+        match json {
+            true => println!("{}", self.json),
+            false => println!("{}", self.text),
+        }
+    }
+}
+
+fn sign(args: &SignArgs) -> Result<Output, String> {
+    let private_key = read(&args.key)?;
+    let body = args.request.read_body()?;
+
+    let mut signer = truelayer_signing::sign_with_pem(&args.kid, &private_key)
+        .method(args.request.method)
+        .path(&args.request.path)
+        .headers(args.request.headers())
+        .body(&body);
+    if let Some(jku) = &args.jku {
+        signer = signer.jku(jku);
+    }
+    let tl_signature = signer.build_signer().sign().map_err(|e| e.reason())?;
+
+    Ok(Output {
+        success: true,
+        json: json!({ "tl_signature": tl_signature }),
+        text: tl_signature,
+    })
+}
+
+fn verify(args: &VerifyArgs) -> Result<Output, String> {
+    let body = args.request.read_body()?;
+    let public_key = match (&args.public_key.pem, &args.public_key.jwks) {
+        (Some(pem), _) => PublicKeyFile::Pem(read(pem)?),
+        (_, Some(jwks)) => PublicKeyFile::Jwks(read(jwks)?),
+        (None, None) => return Err("one of --pem or --jwks is required".into()),
+    };
+    let verifier = match &public_key {
+        PublicKeyFile::Pem(pem) => truelayer_signing::verify_with_pem(pem),
+        PublicKeyFile::Jwks(jwks) => truelayer_signing::verify_with_jwks(jwks),
+    };
+
+    let verifier = verifier
+        .method(args.request.method)
+        .path(&args.request.path)
+        .headers(args.request.headers())
+        .trailing_slash(if args.strict_trailing_slash {
+            TrailingSlash::Strict
+        } else {
+            TrailingSlash::Tolerant
+        });
+    let result = args
+        .require_header
+        .iter()
+        .fold(verifier, |verifier, header| verifier.require_header(header))
+        .body(&body)
+        .build_verifier()
+        .verify_diagnostic(&args.signature, DiagnosticBody::Digest);
+
+    Ok(match result {
+        Ok(verified) => Output {
+            success: true,
+            text: match &verified.signed_path {
+                Some(signed_path) => format!("valid, signed path {signed_path}"),
+                None => "valid".into(),
+            },
+            json: json!({
+                "valid": true,
+                "kid": verified.kid,
+                "signed_headers": verified.signed_headers,
+                "signed_path": verified.signed_path,
+            }),
+        },
+        Err(diagnostics) => {
+            let reason = diagnostics.error.reason();
+            let mut text = format!("invalid: {reason}");
+            if let Some(payload) = &diagnostics.signing_payload {
+                text.push_str("\nsigning payload:\n");
+                text.push_str(payload);
+            }
+            Output {
+                success: false,
+                text,
+                json: json!({
+                    "valid": false,
+                    "reason": reason,
+                    "signing_payload": diagnostics.signing_payload,
+                }),
+            }
+        }
+    })
+}
+
+fn inspect(args: &InspectArgs) -> Result<Output, String> {
+    let header = truelayer_signing::extract_jws_header(&args.signature).map_err(|e| e.reason())?;
+    let signed_headers: Vec<&str> = header
+        .tl_headers
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|h| !h.is_empty())
+        .collect();
+
+    let signing_payload = match &args.request() {
+        Some(request) => Some(signing_payload(&header.kid, &signed_headers, request)?),
+        None => None,
+    };
+
+    let mut text = format!(
+        "alg: {:?}\nkid: {}\ntl_version: {}\ntl_headers: {}\njku: {}",
+        header.alg,
+        header.kid,
+        header
+            .tl_version
+            .map_or_else(String::new, |v| v.to_string()),
+        signed_headers.join(","),
+        header.jku.as_deref().unwrap_or_default(),
+    );
+    if let Some(payload) = &signing_payload {
+        text.push_str("\nsigning payload:\n");
+        text.push_str(payload);
+    }
+
+    Ok(Output {
+        success: true,
+        json: json!({
+            "header": header,
+            "signed_headers": signed_headers,
+            "signing_payload": signing_payload,
+        }),
+        text,
+    })
+}
+
+/// Build the v2 signing payload for the request using the signature's signed headers.
+fn signing_payload(
+    kid: &str,
+    signed_headers: &[&str],
+    request: &RequestArgs,
+) -> Result<String, String> {
+    let body = request.read_body()?;
+    let headers = signed_headers
+        .iter()
+        .map(|name| {
+            request
+                .headers()
+                .find(|(h, _)| h.eq_ignore_ascii_case(name))
+                .map(|(_, value)| (*name, value))
+                .ok_or_else(|| format!("missing signed header {name}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let payload = SignerBuilder::new()
+        .kid(kid)
+        .method(request.method)
+        .path(&request.path)
+        .headers(headers)
+        .body(&body)
+        .build_custom_signer()
+        .signing_payload();
+    Ok(String::from_utf8_lossy(&payload).into_owned())
+}
+
+fn read(path: &PathBuf) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("reading {}: {e}", path.display()))
+}
+
+fn parse_method(method: &str) -> Result<Method, String> {
+    Method::from_name(&method.to_ascii_uppercase()).map_err(|e| e.to_string())
+}
+
+fn parse_path(path: &str) -> Result<String, String> {
+    match path.starts_with('/') {
+        true => Ok(path.to_owned()),
+        false => Err("path must start with '/'".into()),
+    }
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or("expected header \"Name: value\"")?;
+    Ok((name.trim().to_owned(), value.trim().to_owned()))
+}
@@ -0,0 +1,161 @@
+//! ES512 crypto backend, selected by the `openssl` or `rust-crypto` cargo features.
+//!
+//! Each backend provides P-521 key parsing & ES512 signing/verification
+//! using fixed length 132-byte `r || s` signatures.
+#[cfg(not(any(feature = "openssl", feature = "rust-crypto")))]
+compile_error!("truelayer-signing requires either the `openssl` or `rust-crypto` feature");
+
+#[cfg(feature = "openssl")]
+mod openssl;
+#[cfg(all(feature = "rust-crypto", not(feature = "openssl")))]
+mod rust_crypto;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{BoxError, Error};
+
+#[cfg(feature = "openssl")]
+use self::openssl as backend;
+#[cfg(all(feature = "rust-crypto", not(feature = "openssl")))]
+use self::rust_crypto as backend;
+
+pub(crate) use backend::{
+    ec_public_key, generate_ec_private_key, parse_ec_private_key, parse_ec_private_key_encrypted,
+    parse_ec_private_key_pkcs8_der, parse_ec_private_key_sec1_der, parse_ec_public_key,
+    parse_ec_public_key_spki_der, private_key_to_pkcs8_pem, private_key_to_sec1_pem,
+    public_key_to_pem, sha256, sign_es512, verify_es512, EcPrivateKey, EcPublicKey,
+};
+
+/// Random (v4) uuid string, used as a generated `kid`.
+pub(crate) fn random_kid() -> Result<String, BoxError> {
+    let mut bytes = [0u8; 16];
+    backend::fill_random(&mut bytes)?;
+    // version 4 & RFC 4122 variant bits
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Public P-521 JWK json, with 66-byte `x` & `y` coordinates.
+pub(crate) fn ec_public_jwk(kid: &str, key: &EcPublicKey) -> Result<serde_json::Value, BoxError> {
+    let (x, y) = backend::public_key_coordinates(key)?;
+    Ok(serde_json::json!({
+        "kty": "EC",
+        "alg": "ES512",
+        "kid": kid,
+        "crv": "P-521",
+        "x": URL_SAFE_NO_PAD.encode(x),
+        "y": URL_SAFE_NO_PAD.encode(y),
+    }))
+}
+
+/// Read JWKs json then find & parse the JWK for the given `signature_kid`
+pub(crate) fn find_and_parse_ec_jwk(
+    signature_kid: &str,
+    jwks: &[u8],
+) -> Result<EcPublicKey, Error> {
+    Jwks::parse(jwks)?
+        .find(|k| k.kid == signature_kid)
+        .ok_or_else(|| Error::UnknownKid(signature_kid.to_owned()))?
+        .parse_p521()
+        .map_err(Error::InvalidKey)
+}
+
+/// Read JWKs json & parse all P-521 JWKs, returning `(kid, key)` pairs.
+///
+/// JWKs with other key types or curves, or that fail to parse, are ignored.
+pub(crate) fn parse_ec_jwks(jwks: &[u8]) -> Result<Vec<(String, EcPublicKey)>, Error> {
+    Ok(Jwks::parse(jwks)?
+        .filter(|k| k.kty == "EC" && k.crv == "P-521")
+        .filter_map(|k| {
+            let kid = k.kid.clone();
+            let key = k.parse_p521().ok()?;
+            Some((kid, key))
+        })
+        .collect())
+}
+
+/// Read a private JWK json, with the `d` parameter, returning the `kid` & key.
+pub(crate) fn parse_ec_private_jwk(jwk: &[u8]) -> Result<(String, EcPrivateKey), Error> {
+    let jwk: Jwk = serde_json::from_slice(jwk).map_err(|e| Error::InvalidKey(e.into()))?;
+    let kid = jwk.kid.clone();
+    let key = jwk.parse_p521_private().map_err(Error::InvalidKey)?;
+    Ok((kid, key))
+}
+
+/// JWKs json response.
+#[derive(serde::Deserialize)]
+struct Jwks {
+    #[serde(default)]
+    keys: Vec<serde_json::Value>,
+}
+
+impl Jwks {
+    /// Parse JWKs json, skipping malformed JWKs so one bad key does not
+    /// fail the whole set.
+    fn parse(jwks: &[u8]) -> Result<impl Iterator<Item = Jwk>, Error> {
+        let jwks: Self = serde_json::from_slice(jwks).map_err(|e| Error::InvalidKey(e.into()))?;
+        Ok(jwks
+            .keys
+            .into_iter()
+            .filter_map(|k| serde_json::from_value(k).ok()))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Jwk {
+    #[serde(default)]
+    kid: String,
+    #[serde(default)]
+    kty: String,
+    #[serde(default)]
+    crv: String,
+    #[serde(default)]
+    x: String,
+    #[serde(default)]
+    y: String,
+    /// Private key parameter.
+    #[serde(default)]
+    d: String,
+}
+
+impl Jwk {
+    fn parse_p521(self) -> Result<EcPublicKey, BoxError> {
+        if self.kty != "EC" {
+            return Err("unsupported jwk kty".into());
+        }
+        if self.crv != "P-521" {
+            return Err("unsupported jwk crv".into());
+        }
+
+        let x = URL_SAFE_NO_PAD.decode(self.x)?;
+        let y = URL_SAFE_NO_PAD.decode(self.y)?;
+        backend::p521_public_key(&x, &y)
+    }
+
+    /// Parse the private key `d`, checking it matches the public `x` & `y`.
+    fn parse_p521_private(self) -> Result<EcPrivateKey, BoxError> {
+        if self.d.is_empty() {
+            return Err("jwk is missing private key parameter d".into());
+        }
+        let d = URL_SAFE_NO_PAD.decode(&self.d)?;
+        let private_key = backend::p521_private_key(&d)?;
+
+        let public_key = self.parse_p521()?;
+        let public_coordinates = backend::public_key_coordinates(&public_key)?;
+        let expected = backend::public_key_coordinates(&ec_public_key(&private_key)?)?;
+        if public_coordinates != expected {
+            return Err("jwk x & y do not match private key d".into());
+        }
+        Ok(private_key)
+    }
+}
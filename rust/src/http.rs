@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     fmt,
     hash::{Hash, Hasher},
 };
@@ -23,6 +24,18 @@ impl Method {
             Method::Patch => "PATCH",
         }
     }
+
+    /// Returns the method with the given name, e.g. `"POST"`.
+    pub fn from_name(name: &str) -> Result<Self, crate::Error> {
+        match name {
+            "GET" => Ok(Method::Get),
+            "POST" => Ok(Method::Post),
+            "PUT" => Ok(Method::Put),
+            "DELETE" => Ok(Method::Delete),
+            "PATCH" => Ok(Method::Patch),
+            _ => Err(crate::Error::UnsupportedMethod(name.to_owned())),
+        }
+    }
 }
 
 impl std::fmt::Display for Method {
@@ -33,8 +46,22 @@ impl std::fmt::Display for Method {
 
 /// A case-sensitive header name, with case-insensitive
 /// `Eq` & `Hash` implementations.
-#[derive(Clone, Copy, Eq)]
-pub(crate) struct HeaderName<'a>(pub &'a str);
+#[derive(Clone, Eq)]
+pub(crate) struct HeaderName<'a>(pub Cow<'a, str>);
+
+impl<'a> HeaderName<'a> {
+    pub(crate) fn new(name: &'a str) -> Self {
+        Self(Cow::Borrowed(name))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn into_owned(self) -> HeaderName<'static> {
+        HeaderName(Cow::Owned(self.0.into_owned()))
+    }
+}
 
 impl fmt::Debug for HeaderName<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -60,21 +87,21 @@ impl Hash for HeaderName<'_> {
 /// Case-insensitive equals.
 impl PartialEq for HeaderName<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.0.eq_ignore_ascii_case(other.0)
+        self.0.eq_ignore_ascii_case(&other.0)
     }
 }
 
 #[test]
 fn case_insensitive_eq() {
-    let a = HeaderName("X-Custom");
-    let b = HeaderName("x-custom");
+    let a = HeaderName::new("X-Custom");
+    let b = HeaderName::new("x-custom");
     assert_eq!(a, b);
 }
 
 #[test]
 fn case_insensitive_hash() {
-    let a = HeaderName("X-Custom");
-    let b = HeaderName("x-custom");
+    let a = HeaderName::new("X-Custom");
+    let b = HeaderName::new("x-custom");
 
     let hash = |thing: HeaderName<'_>| {
         let mut s = std::collections::hash_map::DefaultHasher::new();
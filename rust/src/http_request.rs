@@ -0,0 +1,173 @@
+use ::http::{header::HeaderValue, HeaderMap, Request};
+
+use crate::{
+    verify_config::check_signed_headers_unique, Error, Method, SigningKey, VerifiedSignature,
+    VerifyConfig, VerifyingKeySet,
+};
+
+/// `Tl-Signature` header name.
+const TL_SIGNATURE: &str = "Tl-Signature";
+
+/// Sign a http request, inserting the `Tl-Signature` header.
+///
+/// The signature includes the request method, path, body & the given `signed_headers`,
+/// which must be present in the request.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (signing_key, idempotency_key, body): (truelayer_signing::SigningKey, &str, Vec<u8>) = unimplemented!();
+/// let mut request = http::Request::post("https://api.truelayer.com/payouts")
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .unwrap();
+///
+/// truelayer_signing::sign_request(&signing_key, &mut request, &["Idempotency-Key"])?;
+/// # Ok(()) }
+/// ```
+pub fn sign_request<B: AsRef<[u8]>>(
+    signing_key: &SigningKey,
+    request: &mut Request<B>,
+    signed_headers: &[&str],
+) -> Result<(), Error> {
+    let method = Method::try_from(request.method())?;
+    let headers = signed_headers
+        .iter()
+        .map(|name| match request.headers().get(*name) {
+            Some(value) => Ok((*name, value.as_bytes())),
+            None => Err(Error::MissingRequiredHeader((*name).to_owned())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tl_signature = crate::sign_with_key(signing_key)
+        .method(method)
+        .path(request.uri().path())
+        .headers(headers)
+        .body(request.body().as_ref())
+        .build_signer()
+        .sign()?;
+
+    let tl_signature =
+        HeaderValue::try_from(tl_signature).map_err(|e| Error::SigningFailed(e.into()))?;
+    request.headers_mut().insert(TL_SIGNATURE, tl_signature);
+    Ok(())
+}
+
+/// Verify a http request's `Tl-Signature` header using the request method,
+/// path, headers & body.
+///
+/// Headers declared in the signature must have a single value in the request.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (jwks, request): (&[u8], http::Request<Vec<u8>>) = unimplemented!();
+/// let keys = truelayer_signing::VerifyingKeySet::from_jwks(jwks)?;
+///
+/// truelayer_signing::verify_request(&keys, &request)?;
+/// # Ok(()) }
+/// ```
+pub fn verify_request<B: AsRef<[u8]>>(
+    keys: &VerifyingKeySet,
+    request: &Request<B>,
+) -> Result<VerifiedSignature, Error> {
+    verify_parts(
+        keys,
+        request.method(),
+        request.uri().path(),
+        request.headers(),
+        request.body().as_ref(),
+    )
+}
+
+/// Verify the `Tl-Signature` header of a request's parts & buffered body using the
+/// given config, e.g. with required headers & a trailing slash policy.
+///
+/// Headers declared in the signature must have a single value in the request.
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> Result<(), truelayer_signing::Error> {
+/// # let (keys, request): (truelayer_signing::VerifyingKeySet, http::Request<Vec<u8>>) = unimplemented!();
+/// let config = truelayer_signing::VerifyConfig::new(keys).require_header("Idempotency-Key");
+///
+/// let (parts, body) = request.into_parts();
+/// truelayer_signing::verify_request_parts(&parts, &body, &config).await?;
+/// # Ok(()) }
+/// ```
+pub async fn verify_request_parts(
+    parts: &::http::request::Parts,
+    body: &[u8],
+    config: &VerifyConfig,
+) -> Result<VerifiedSignature, Error> {
+    let headers = parts
+        .headers
+        .iter()
+        .map(|(h, v)| (h.as_str(), v.as_bytes()));
+    config
+        .verify(parts.method.as_str(), parts.uri.path(), headers, body)
+        .await
+}
+
+/// Verify the `Tl-Signature` header of a request's constituent parts.
+fn verify_parts(
+    keys: &VerifyingKeySet,
+    method: &::http::Method,
+    path: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<VerifiedSignature, Error> {
+    let tl_signature = tl_signature(headers)?;
+    let headers: Vec<_> = headers
+        .iter()
+        .map(|(h, v)| (h.as_str(), v.as_bytes()))
+        .collect();
+    check_signed_headers_unique(&headers, tl_signature)?;
+    crate::verify_with_key_set(keys)
+        .method(Method::try_from(method)?)
+        .path(path)
+        .headers(headers)
+        .body(body)
+        .build_verifier()
+        .verify(tl_signature)
+}
+
+/// Returns the `Tl-Signature` header value.
+fn tl_signature(headers: &HeaderMap) -> Result<&str, Error> {
+    headers
+        .get(TL_SIGNATURE)
+        .ok_or_else(|| Error::malformed_signature("missing Tl-Signature header"))?
+        .to_str()
+        .map_err(|e| Error::MalformedSignature {
+            reason: "invalid Tl-Signature header value",
+            source: Some(e.into()),
+        })
+}
+
+impl TryFrom<&::http::Method> for Method {
+    type Error = Error;
+
+    fn try_from(method: &::http::Method) -> Result<Self, Self::Error> {
+        Method::from_name(method.as_str())
+    }
+}
+
+impl TryFrom<::http::Method> for Method {
+    type Error = Error;
+
+    fn try_from(method: ::http::Method) -> Result<Self, Self::Error> {
+        Method::try_from(&method)
+    }
+}
+
+impl From<Method> for ::http::Method {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Get => ::http::Method::GET,
+            Method::Post => ::http::Method::POST,
+            Method::Put => ::http::Method::PUT,
+            Method::Delete => ::http::Method::DELETE,
+            Method::Patch => ::http::Method::PATCH,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, VerifyingKeySet};
+
+use super::{cache_max_age, jku_and_kid, JwksCache, DEFAULT_TIMEOUT};
+
+/// Blocking version of [`JwksProvider`](crate::JwksProvider), fetches & caches
+/// the jwks used to verify webhook signatures.
+///
+/// Note: As with [`reqwest::blocking::Client`] this must not be used within an
+/// async runtime.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// let jwks_provider = truelayer_signing::BlockingJwksProvider::new();
+///
+/// // fetch, or use cached, keys for the signature's jku
+/// let keys = jwks_provider.keys_for_signature(tl_signature)?;
+///
+/// truelayer_signing::verify_with_key_set(&keys)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/webhook")
+///     .headers(headers)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockingJwksProvider {
+    client: reqwest::blocking::Client,
+    cache: JwksCache,
+}
+
+impl Default for BlockingJwksProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockingJwksProvider {
+    /// Create a new provider allowing the TrueLayer production & sandbox webhook jwks urls.
+    pub fn new() -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .expect("reqwest::blocking::Client::build");
+        Self::with_client(client)
+    }
+
+    /// Create a new provider using the given http client.
+    ///
+    /// Note: The client should not follow redirects, as these are not
+    /// subject to the `jku` allow-list.
+    pub fn with_client(client: reqwest::blocking::Client) -> Self {
+        Self {
+            client,
+            cache: JwksCache::default(),
+        }
+    }
+
+    /// Replace the `jku` allow-list.
+    pub fn allowed_jkus(mut self, jkus: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.cache.allowed_jkus = jkus.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add a url to the `jku` allow-list.
+    pub fn allow_jku(mut self, jku: impl Into<String>) -> Self {
+        self.cache.allowed_jkus.insert(jku.into());
+        self
+    }
+
+    /// How long to cache jwks responses without a `Cache-Control` `max-age`.
+    ///
+    /// Default 10 minutes. Zero disables caching of these responses.
+    pub fn fallback_max_age(mut self, max_age: Duration) -> Self {
+        self.cache.fallback_max_age = max_age;
+        self
+    }
+
+    /// Minimum time between refetching a cached jwks because of an unknown `kid`.
+    ///
+    /// Default 10s.
+    pub fn min_refetch_interval(mut self, interval: Duration) -> Self {
+        self.cache.min_refetch_interval = interval;
+        self
+    }
+
+    /// Returns the keys from the `jku` jwks, fetching if not cached.
+    ///
+    /// If the cached keys do not include `kid` the jwks will be refetched.
+    pub fn keys(&self, jku: &str, kid: &str) -> Result<Arc<VerifyingKeySet>, Error> {
+        self.cache.check_allowed(jku)?;
+        if let Some(keys) = self.cache.get(jku, kid) {
+            return Ok(keys);
+        }
+
+        let requested_at = Instant::now();
+        let fetch_lock = self.cache.fetch_lock(jku);
+        let _fetching = fetch_lock.blocking_lock();
+        if let Some(keys) = self.cache.fetched_since(jku, requested_at) {
+            return Ok(keys);
+        }
+
+        let response = self
+            .client
+            .get(jku)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::JwksFetch(e.into()))?;
+        let max_age = cache_max_age(response.headers());
+        let jwks = response.bytes().map_err(|e| Error::JwksFetch(e.into()))?;
+
+        self.cache.insert(jku, &jwks, max_age)
+    }
+
+    /// Returns the keys from the jwks of the given `Tl-Signature` header value's `jku`,
+    /// fetching if not cached.
+    pub fn keys_for_signature(&self, tl_signature: &str) -> Result<Arc<VerifyingKeySet>, Error> {
+        let (jku, kid) = jku_and_kid(tl_signature)?;
+        self.keys(&jku, &kid)
+    }
+}
@@ -0,0 +1,316 @@
+#[cfg(feature = "jwks-client-blocking")]
+mod blocking;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use indexmap::IndexSet;
+use reqwest::header::{HeaderMap, CACHE_CONTROL};
+
+use crate::{Error, VerifyingKeySet};
+
+#[cfg(feature = "jwks-client-blocking")]
+pub use self::blocking::BlockingJwksProvider;
+
+/// Default `jku` allow-list, the TrueLayer production & sandbox webhook jwks urls.
+const TRUELAYER_JKUS: [&str; 2] = [
+    "https://webhooks.truelayer.com/.well-known/jwks",
+    "https://webhooks.truelayer-sandbox.com/.well-known/jwks",
+];
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_FALLBACK_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Fetches & caches the jwks used to verify webhook signatures.
+///
+/// * Only `jku` urls in the allow-list are fetched, by default the TrueLayer
+///   production & sandbox webhook jwks urls.
+/// * Responses are cached according to their `Cache-Control` `max-age`, or
+///   [`JwksProvider::fallback_max_age`] without one.
+/// * A signature with an unknown `kid` causes the jwks to be refetched once,
+///   at most every [`JwksProvider::min_refetch_interval`], to pick up rotated keys.
+/// * Concurrent requests for the same `jku` share a single fetch.
+///
+/// Cloning is cheap & clones share the same cache.
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> Result<(), truelayer_signing::Error> {
+/// # let (body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// let jwks_provider = truelayer_signing::JwksProvider::new();
+///
+/// // fetch, or use cached, keys for the signature's jku
+/// let keys = jwks_provider.keys_for_signature(tl_signature).await?;
+///
+/// truelayer_signing::verify_with_key_set(&keys)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/webhook")
+///     .headers(headers)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwksProvider {
+    client: reqwest::Client,
+    cache: JwksCache,
+}
+
+impl Default for JwksProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JwksProvider {
+    /// Create a new provider allowing the TrueLayer production & sandbox webhook jwks urls.
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .expect("reqwest::Client::build");
+        Self::with_client(client)
+    }
+
+    /// Create a new provider using the given http client.
+    ///
+    /// Note: The client should not follow redirects, as these are not
+    /// subject to the `jku` allow-list.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            cache: JwksCache::default(),
+        }
+    }
+
+    /// Replace the `jku` allow-list.
+    pub fn allowed_jkus(mut self, jkus: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.cache.allowed_jkus = jkus.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add a url to the `jku` allow-list.
+    pub fn allow_jku(mut self, jku: impl Into<String>) -> Self {
+        self.cache.allowed_jkus.insert(jku.into());
+        self
+    }
+
+    /// How long to cache jwks responses without a `Cache-Control` `max-age`.
+    ///
+    /// Default 10 minutes. Zero disables caching of these responses.
+    pub fn fallback_max_age(mut self, max_age: Duration) -> Self {
+        self.cache.fallback_max_age = max_age;
+        self
+    }
+
+    /// Minimum time between refetching a cached jwks because of an unknown `kid`.
+    ///
+    /// Default 10s.
+    pub fn min_refetch_interval(mut self, interval: Duration) -> Self {
+        self.cache.min_refetch_interval = interval;
+        self
+    }
+
+    /// Returns the keys from the `jku` jwks, fetching if not cached.
+    ///
+    /// If the cached keys do not include `kid` the jwks will be refetched.
+    pub async fn keys(&self, jku: &str, kid: &str) -> Result<Arc<VerifyingKeySet>, Error> {
+        self.cache.check_allowed(jku)?;
+        if let Some(keys) = self.cache.get(jku, kid) {
+            return Ok(keys);
+        }
+
+        let requested_at = Instant::now();
+        let fetch_lock = self.cache.fetch_lock(jku);
+        let _fetching = fetch_lock.lock().await;
+        if let Some(keys) = self.cache.fetched_since(jku, requested_at) {
+            return Ok(keys);
+        }
+
+        let response = self
+            .client
+            .get(jku)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::JwksFetch(e.into()))?;
+        let max_age = cache_max_age(response.headers());
+        let jwks = response
+            .bytes()
+            .await
+            .map_err(|e| Error::JwksFetch(e.into()))?;
+
+        self.cache.insert(jku, &jwks, max_age)
+    }
+
+    /// Returns the keys from the jwks of the given `Tl-Signature` header value's `jku`,
+    /// fetching if not cached.
+    pub async fn keys_for_signature(
+        &self,
+        tl_signature: &str,
+    ) -> Result<Arc<VerifyingKeySet>, Error> {
+        let (jku, kid) = jku_and_kid(tl_signature)?;
+        self.keys(&jku, &kid).await
+    }
+}
+
+/// Jwks cache & `jku` allow-list shared by [`JwksProvider`] & [`BlockingJwksProvider`].
+#[derive(Clone)]
+struct JwksCache {
+    allowed_jkus: IndexSet<String>,
+    fallback_max_age: Duration,
+    min_refetch_interval: Duration,
+    entries: Arc<Mutex<HashMap<String, CachedJwks>>>,
+    /// Per `jku` lock held while fetching, so concurrent misses share one fetch.
+    fetch_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl fmt::Debug for JwksCache {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("JwksCache")
+            .field("allowed_jkus", &self.allowed_jkus)
+            .field("fallback_max_age", &self.fallback_max_age)
+            .field("min_refetch_interval", &self.min_refetch_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for JwksCache {
+    fn default() -> Self {
+        Self {
+            allowed_jkus: TRUELAYER_JKUS.into_iter().map(String::from).collect(),
+            fallback_max_age: DEFAULT_FALLBACK_MAX_AGE,
+            min_refetch_interval: DEFAULT_MIN_REFETCH_INTERVAL,
+            entries: <_>::default(),
+            fetch_locks: <_>::default(),
+        }
+    }
+}
+
+struct CachedJwks {
+    keys: Arc<VerifyingKeySet>,
+    fetched_at: Instant,
+    max_age: Duration,
+}
+
+impl JwksCache {
+    fn check_allowed(&self, jku: &str) -> Result<(), Error> {
+        match self.allowed_jkus.contains(jku) {
+            true => Ok(()),
+            false => Err(Error::UnpermittedJku(jku.to_owned())),
+        }
+    }
+
+    /// Returns cached keys unless expired, or missing `kid` & due a refetch.
+    fn get(&self, jku: &str, kid: &str) -> Option<Arc<VerifyingKeySet>> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = entries.get(jku)?;
+        let age = entry.fetched_at.elapsed();
+        if age >= entry.max_age {
+            return None;
+        }
+        match entry.keys.get(kid) {
+            Some(_) => Some(entry.keys.clone()),
+            None if age < self.min_refetch_interval => Some(entry.keys.clone()),
+            None => None,
+        }
+    }
+
+    /// Returns keys fetched at, or after, `instant` regardless of expiry, i.e.
+    /// by a concurrent fetch completed while waiting for the [`JwksCache::fetch_lock`].
+    fn fetched_since(&self, jku: &str, instant: Instant) -> Option<Arc<VerifyingKeySet>> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = entries.get(jku)?;
+        (entry.fetched_at >= instant).then(|| entry.keys.clone())
+    }
+
+    /// Returns the lock to hold while fetching the `jku` jwks.
+    fn fetch_lock(&self, jku: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.fetch_locks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(jku.to_owned())
+            .or_default()
+            .clone()
+    }
+
+    /// Parse & cache a fetched jwks response.
+    fn insert(
+        &self,
+        jku: &str,
+        jwks: &[u8],
+        max_age: Option<Duration>,
+    ) -> Result<Arc<VerifyingKeySet>, Error> {
+        let keys = Arc::new(VerifyingKeySet::from_jwks(jwks)?);
+        let entry = CachedJwks {
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+            max_age: max_age.unwrap_or(self.fallback_max_age),
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(jku.to_owned(), entry);
+        Ok(keys)
+    }
+}
+
+/// Extract the `(jku, kid)` from a `Tl-Signature` header value.
+fn jku_and_kid(tl_signature: &str) -> Result<(String, String), Error> {
+    let header = crate::extract_jws_header(tl_signature)?;
+    let jku = header
+        .jku
+        .ok_or_else(|| Error::malformed_signature("missing header jku"))?;
+    Ok((jku.into_owned(), header.kid.into_owned()))
+}
+
+/// Parse the `Cache-Control` `max-age`, `no-cache` & `no-store` are treated as zero.
+fn cache_max_age(headers: &HeaderMap) -> Option<Duration> {
+    let cache_control = headers.get(CACHE_CONTROL)?.to_str().ok()?;
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+        if directive.eq_ignore_ascii_case("no-cache") || directive.eq_ignore_ascii_case("no-store")
+        {
+            return Some(Duration::ZERO);
+        }
+        if let Some((name, value)) = directive.split_once('=') {
+            if name.trim().eq_ignore_ascii_case("max-age") {
+                max_age = value
+                    .trim()
+                    .trim_matches('"')
+                    .parse()
+                    .ok()
+                    .map(Duration::from_secs);
+            }
+        }
+    }
+    max_age
+}
+
+#[test]
+fn cache_control_max_age() {
+    let max_age = |value: &'static str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(CACHE_CONTROL, value.parse().unwrap());
+        cache_max_age(&headers)
+    };
+
+    assert_eq!(max_age("max-age=600"), Some(Duration::from_secs(600)));
+    assert_eq!(
+        max_age("public, Max-Age=\"60\", must-revalidate"),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(max_age("max-age=600, no-store"), Some(Duration::ZERO));
+    assert_eq!(max_age("no-cache"), Some(Duration::ZERO));
+    assert_eq!(max_age("public"), None);
+    assert_eq!(max_age("max-age=soon"), None);
+    assert_eq!(cache_max_age(&HeaderMap::new()), None);
+}
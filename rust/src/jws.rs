@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
 
 use indexmap::IndexMap;
 
@@ -27,14 +27,14 @@ pub struct JwsHeader<'a> {
 impl<'a> JwsHeader<'a> {
     pub(crate) fn new_v2(
         kid: &'a str,
-        headers: &IndexMap<HeaderName<'_>, &[u8]>,
+        headers: &IndexMap<HeaderName<'_>, impl AsRef<[u8]>>,
         jku: Option<&'a str>,
     ) -> Self {
         let header_keys = headers.keys().fold(String::new(), |mut all, next| {
             if !all.is_empty() {
                 all.push(',');
             }
-            all.push_str(next.0);
+            all.push_str(next.as_str());
             all
         });
         Self {
@@ -45,6 +45,16 @@ impl<'a> JwsHeader<'a> {
             jku: jku.map(Cow::Borrowed),
         }
     }
+
+    pub(crate) fn into_owned(self) -> JwsHeader<'static> {
+        JwsHeader {
+            alg: self.alg,
+            kid: Cow::Owned(self.kid.into_owned()),
+            tl_version: self.tl_version,
+            tl_headers: self.tl_headers,
+            jku: self.jku.map(|jku| Cow::Owned(jku.into_owned())),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -55,6 +65,16 @@ pub enum TlVersion {
     V2,
 }
 
+/// Displays the `tl_version` value, e.g. `2`.
+impl fmt::Display for TlVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V1 => f.write_str("1"),
+            Self::V2 => f.write_str("2"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum JwsAlgorithm {
     #[serde(rename = "ES512")]
@@ -1,18 +1,61 @@
 //! Produce & verify TrueLayer API `Tl-Signature` request headers.
+#[cfg(feature = "actix-web")]
+mod actix;
+#[cfg(feature = "axum")]
+mod axum;
 mod base64;
+mod crypto;
 mod http;
+#[cfg(feature = "http")]
+mod http_request;
+#[cfg(feature = "jwks-client")]
+mod jwks;
 mod jws;
-mod openssl;
+mod owned;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
 mod sign;
+#[cfg(feature = "reqwest-middleware")]
+mod signing_middleware;
+#[cfg(feature = "tower")]
+mod tower;
 mod verify;
+mod verify_config;
 
+#[cfg(feature = "actix-web")]
+pub use actix::{ActixVerifySignature, ActixVerifySignatureService};
+#[cfg(feature = "axum")]
+pub use axum::VerifiedBody;
 pub use http::Method;
+#[cfg(feature = "http")]
+pub use http_request::{sign_request, verify_request, verify_request_parts};
+#[cfg(feature = "jwks-client-blocking")]
+pub use jwks::BlockingJwksProvider;
+#[cfg(feature = "jwks-client")]
+pub use jwks::JwksProvider;
 pub use jws::{JwsAlgorithm, JwsHeader, TlVersion};
-pub use sign::{CustomSigner, Signer, SignerBuilder};
+pub use owned::IntoOwned;
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::{Pkcs11Key, Pkcs11Signer};
+pub use sign::{
+    AsyncEs512Signer, CustomSigner, Es512Signature, Es512Signer, RequestSigner, Signer,
+    SignerBuilder, SigningKey,
+};
+#[cfg(feature = "reqwest-middleware")]
+pub use signing_middleware::SigningMiddleware;
+use std::borrow::Cow;
+#[cfg(feature = "tower")]
+pub use tower::{VerifySignature, VerifySignatureLayer};
 use verify::PublicKey;
-pub use verify::{CustomVerifier, Verifier, VerifierBuilder};
+pub use verify::{
+    AsyncReplayGuard, CustomVerifier, DiagnosticBody, InMemoryReplayGuard, ReplayGuard, ReplayKey,
+    RequestVerifier, TrailingSlash, VerifiedSignature, Verifier, VerifierBuilder,
+    VerifyDiagnostics, VerifyingKey, VerifyingKeySet, VersionPolicy, WebhookVerifier,
+};
+pub use verify_config::VerifyConfig;
 
 /// A utility unit type to denote an item hasn't been set.
+#[derive(Debug, Clone, Copy)]
 pub struct Unset;
 
 /// Start building a request `Tl-Signature` header value using private key
@@ -34,10 +77,54 @@ pub struct Unset;
 pub fn sign_with_pem<'a>(
     kid: &'a str,
     private_key_pem: &'a [u8],
-) -> SignerBuilder<'a, &'a str, &'a [u8], Unset, Unset, Unset> {
+) -> SignerBuilder<'a, Cow<'a, str>, Cow<'a, [u8]>, Unset, Unset, Unset> {
     SignerBuilder::build_with_pem(kid, private_key_pem)
 }
 
+/// Start building a request `Tl-Signature` header value using a pre-parsed
+/// [`SigningKey`].
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (kid, private_key, idempotency_key, body): (&str, _, _, _) = unimplemented!();
+/// let signing_key = truelayer_signing::SigningKey::from_pem(kid, private_key)?;
+///
+/// let tl_signature = truelayer_signing::sign_with_key(&signing_key)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/payouts")
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .build_signer()
+///     .sign()?;
+/// # Ok(()) }
+/// ```
+pub fn sign_with_key(
+    signing_key: &SigningKey,
+) -> SignerBuilder<'_, Cow<'_, str>, Cow<'_, SigningKey>, Unset, Unset, Unset> {
+    SignerBuilder::build_with_key(signing_key)
+}
+
+/// Generate a new P-521 key pair with a random uuid `kid`.
+///
+/// The private key may be exported as pem for storage, & the public key
+/// as pem or a JWK to be registered with TrueLayer.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// let signing_key = truelayer_signing::generate_key_pair()?;
+/// let private_key_pem = signing_key.to_pkcs8_pem()?;
+///
+/// let verifying_key = signing_key.verifying_key()?;
+/// let public_key_pem = verifying_key.to_pem()?;
+/// let public_jwk = verifying_key.to_jwk()?;
+/// # Ok(()) }
+/// ```
+pub fn generate_key_pair() -> Result<SigningKey, Error> {
+    SigningKey::generate()
+}
+
 /// Start building a `Tl-Signature` header verifier using public key pem data.
 ///
 /// # Example
@@ -83,23 +170,132 @@ pub fn verify_with_jwks(jwks: &[u8]) -> VerifierBuilder<'_, PublicKey<'_>, Unset
     VerifierBuilder::jwks(jwks)
 }
 
+/// Start building a `Tl-Signature` header verifier using a pre-parsed [`VerifyingKeySet`].
+///
+/// The key matching the signature `kid` will be used to verify.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (jwks, body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// // parse once & reuse
+/// let keys = truelayer_signing::VerifyingKeySet::from_jwks(jwks)?;
+///
+/// truelayer_signing::verify_with_key_set(&keys)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/webhook")
+///     .headers(headers)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+pub fn verify_with_key_set(
+    keys: &VerifyingKeySet,
+) -> VerifierBuilder<'_, PublicKey<'_>, Unset, Unset, Unset> {
+    VerifierBuilder::key_set(keys)
+}
+
 /// Extract [`JwsHeader`] info from a `Tl-Signature` header value.
 ///
 /// This can then be used to pick a verification key using the `kid` etc.
-pub fn extract_jws_header(tl_signature: &str) -> Result<JwsHeader, Error> {
+pub fn extract_jws_header(tl_signature: &str) -> Result<JwsHeader<'_>, Error> {
     Ok(verify::parse_tl_signature(tl_signature)?.header)
 }
 
+/// Boxed error source.
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Sign/verification error.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
-    /// Key data is invalid.
-    #[error("invalid key: {0}")]
-    InvalidKey(anyhow::Error),
-    /// JWS signature generation or verification failed.
-    #[error("jws signing/verification failed: {0}")]
-    JwsError(anyhow::Error),
+    /// Key data is invalid, e.g. unparseable or not a P-521 key.
+    #[error("invalid key")]
+    InvalidKey(#[source] BoxError),
+    /// No key matches the signature `kid`.
+    #[error("no jwk found for signature kid {0}")]
+    UnknownKid(String),
+    /// The `Tl-Signature` header value could not be parsed, or is missing
+    /// required jws header fields.
+    #[error("malformed signature: {reason}")]
+    MalformedSignature {
+        reason: &'static str,
+        #[source]
+        source: Option<BoxError>,
+    },
+    /// The signature `tl_version` is not supported.
+    #[error("unsupported signature tl_version {0}")]
+    UnsupportedVersion(String),
+    /// A required header is not included in the signature.
+    #[error("signature is missing required header {0}")]
+    MissingRequiredHeader(String),
+    /// A header declared in the signature `tl_headers` is absent from the request.
+    #[error("missing tl_header `{0}` declared in signature")]
+    DeclaredHeaderAbsent(String),
+    /// A header declared in the signature `tl_headers` has multiple values in the request,
+    /// only a single value may be signed.
+    #[error("tl_header `{0}` declared in signature is repeated")]
+    RepeatedHeader(String),
+    /// The signature does not match the request.
+    #[error("signature verification failed")]
+    InvalidSignature(#[source] Option<BoxError>),
+    /// Signature generation failed.
+    #[error("signing failed")]
+    SigningFailed(#[source] BoxError),
+    /// The passphrase could not decrypt the encrypted private key.
+    #[error("incorrect private key passphrase")]
+    IncorrectPassphrase,
+    /// Key pair generation failed.
+    #[error("key generation failed")]
+    KeyGeneration(#[source] BoxError),
+    /// Webhook `X-Tl-Webhook-Timestamp` is not a valid RFC 3339 timestamp.
+    #[error("invalid webhook timestamp")]
+    InvalidWebhookTimestamp(#[source] BoxError),
+    /// Webhook `X-Tl-Webhook-Timestamp` is outside the allowed tolerance of the current time.
+    #[error("webhook timestamp {0} is outside the allowed tolerance")]
+    WebhookTimestampOutOfTolerance(String),
+    /// The signature has already been successfully verified.
+    #[error("replayed signature")]
+    Replayed,
+    /// The replay guard failed to record the request, e.g. a shared store is unavailable.
+    #[error("replay guard failed")]
+    ReplayGuardFailed(#[source] BoxError),
+    /// The signature `jku` is not in the allow-list.
+    #[error("unpermitted jku {0}")]
+    UnpermittedJku(String),
+    /// Fetching the jwks failed.
+    #[error("jwks fetch failed")]
+    JwksFetch(#[source] BoxError),
+    /// The http method is not supported.
+    #[error("unsupported method {0}")]
+    UnsupportedMethod(String),
     /// Other error.
-    #[error("Error: {0}")]
-    Other(anyhow::Error),
+    #[error("other error")]
+    Other(#[source] BoxError),
+}
+
+impl Error {
+    /// Error message including the messages of all sources, e.g.
+    /// `"invalid key: <parse failure>"`.
+    ///
+    /// The [`Display`](std::fmt::Display) message only describes this error, not its cause.
+    pub fn reason(&self) -> String {
+        let mut reason = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            reason.push_str(": ");
+            reason.push_str(&err.to_string());
+            source = err.source();
+        }
+        reason
+    }
+
+    pub(crate) fn malformed_signature(reason: &'static str) -> Self {
+        Self::MalformedSignature {
+            reason,
+            source: None,
+        }
+    }
 }
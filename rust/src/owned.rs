@@ -0,0 +1,40 @@
+use std::borrow::Cow;
+
+use crate::{Method, Unset};
+
+/// Conversion of builder state into an owned `'static` equivalent.
+///
+/// Implemented for the builder type parameters, [`Unset`], [`Method`] & `Cow`.
+/// See [`SignerBuilder::into_owned`](crate::SignerBuilder::into_owned) &
+/// [`VerifierBuilder::into_owned`](crate::VerifierBuilder::into_owned).
+pub trait IntoOwned {
+    /// The owned `'static` type.
+    type Owned: 'static;
+
+    /// Convert into the owned equivalent, cloning any borrowed data.
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl IntoOwned for Unset {
+    type Owned = Unset;
+
+    fn into_owned(self) -> Unset {
+        self
+    }
+}
+
+impl IntoOwned for Method {
+    type Owned = Method;
+
+    fn into_owned(self) -> Method {
+        self
+    }
+}
+
+impl<B: ToOwned + ?Sized + 'static> IntoOwned for Cow<'_, B> {
+    type Owned = Cow<'static, B>;
+
+    fn into_owned(self) -> Cow<'static, B> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
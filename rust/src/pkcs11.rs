@@ -0,0 +1,133 @@
+use std::fmt;
+
+use cryptoki::{
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::Session,
+};
+use sha2::{Digest, Sha512};
+
+use crate::{Error, Es512Signature, Es512Signer};
+
+/// DER encoded secp521r1 (P-521) named curve oid, the expected `CKA_EC_PARAMS`.
+const P521_EC_PARAMS: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x23];
+
+/// Selects a PKCS#11 private key object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pkcs11Key<'a> {
+    /// Key `CKA_LABEL`.
+    Label(&'a str),
+    /// Key `CKA_ID`.
+    Id(&'a [u8]),
+}
+
+/// [`Es512Signer`] using a P-521 private key held in a PKCS#11 token, e.g. an HSM,
+/// so the key never leaves the token.
+///
+/// Signs using `CKM_ECDSA` with the payload SHA-512 hashed by this crate.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let (kid, idempotency_key, body): (&str, &[u8], &[u8]) = unimplemented!();
+/// use cryptoki::{
+///     context::{CInitializeArgs, CInitializeFlags, Pkcs11},
+///     session::UserType,
+///     types::AuthPin,
+/// };
+/// use truelayer_signing::{Pkcs11Key, Pkcs11Signer};
+///
+/// let pkcs11 = Pkcs11::new("/usr/lib/softhsm/libsofthsm2.so")?;
+/// pkcs11.initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))?;
+/// let slot = pkcs11.get_slots_with_token()?.remove(0);
+/// let session = pkcs11.open_ro_session(slot)?;
+/// session.login(UserType::User, Some(&AuthPin::new("1234".into())))?;
+///
+/// let signer = Pkcs11Signer::new(session, Pkcs11Key::Label("tl-signing"))?;
+///
+/// let tl_signature = truelayer_signing::SignerBuilder::new()
+///     .kid(kid)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/payouts")
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .build_custom_signer()
+///     .sign_with_signer(&signer)?;
+/// # Ok(()) }
+/// ```
+pub struct Pkcs11Signer {
+    session: Session,
+    key: ObjectHandle,
+}
+
+impl fmt::Debug for Pkcs11Signer {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Pkcs11Signer")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Pkcs11Signer {
+    /// Find the P-521 private key using a logged in `session`.
+    ///
+    /// Returns [`Error::InvalidKey`] if there is not exactly one matching EC private key,
+    /// or the key is not P-521.
+    pub fn new(session: Session, key: Pkcs11Key<'_>) -> Result<Self, Error> {
+        let template = [
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC),
+            match key {
+                Pkcs11Key::Label(label) => Attribute::Label(label.as_bytes().to_vec()),
+                Pkcs11Key::Id(id) => Attribute::Id(id.to_vec()),
+            },
+        ];
+        let keys = session
+            .find_objects(&template)
+            .map_err(|e| Error::InvalidKey(e.into()))?;
+        let key = match keys[..] {
+            [key] => key,
+            [] => {
+                return Err(Error::InvalidKey(
+                    "no matching PKCS#11 EC private key".into(),
+                ))
+            }
+            _ => {
+                return Err(Error::InvalidKey(
+                    "multiple matching PKCS#11 EC private keys".into(),
+                ))
+            }
+        };
+
+        let ec_params = session
+            .get_attributes(key, &[AttributeType::EcParams])
+            .map_err(|e| Error::InvalidKey(e.into()))?;
+        match ec_params.as_slice() {
+            [Attribute::EcParams(params)] if params == P521_EC_PARAMS => {}
+            _ => {
+                return Err(Error::InvalidKey(
+                    "the underlying elliptic curve must be P-521 to sign using ES512".into(),
+                ))
+            }
+        }
+
+        Ok(Self { session, key })
+    }
+
+    /// The PKCS#11 session.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+}
+
+impl Es512Signer for Pkcs11Signer {
+    fn sign_es512(&self, payload: &[u8]) -> Result<Es512Signature, Error> {
+        let digest = Sha512::digest(payload);
+        let signature = self
+            .session
+            .sign(&Mechanism::Ecdsa, self.key, &digest)
+            .map_err(|e| Error::SigningFailed(e.into()))?;
+        // CKM_ECDSA signatures are fixed length r || s
+        Ok(Es512Signature::Fixed(signature))
+    }
+}
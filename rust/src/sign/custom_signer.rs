@@ -1,10 +1,13 @@
-use std::future::Future;
+use std::{borrow::Cow, future::Future};
 
 use indexmap::IndexMap;
 
-use crate::{base64::ToUrlSafeBase64, http::HeaderName, Error, JwsHeader};
+use crate::{
+    base64::ToUrlSafeBase64, http::HeaderName, owned::IntoOwned, AsyncEs512Signer, Error,
+    Es512Signer, JwsHeader,
+};
 
-use super::build_v2_signing_payload;
+use super::{build_v2_signing_payload, owned_headers};
 
 /// Builder to generate a `Tl-Signature` header value with a custom signer.
 ///
@@ -22,26 +25,36 @@ use super::build_v2_signing_payload;
 ///     .sign_with(|bytes| todo!())?;
 /// # Ok(()) }
 /// ```
+#[derive(Clone)]
 pub struct CustomSigner<'a> {
-    pub(crate) kid: &'a str,
-    pub(crate) body: &'a [u8],
+    pub(crate) kid: Cow<'a, str>,
+    pub(crate) body: Cow<'a, [u8]>,
     pub(crate) method: &'static str,
-    pub(crate) path: &'a str,
-    pub(crate) headers: IndexMap<HeaderName<'a>, &'a [u8]>,
-    pub(crate) jws_jku: Option<&'a str>,
+    pub(crate) path: Cow<'a, str>,
+    pub(crate) headers: IndexMap<HeaderName<'a>, Cow<'a, [u8]>>,
+    pub(crate) jws_jku: Option<Cow<'a, str>>,
 }
 
 impl<'a> CustomSigner<'a> {
     fn build_jws_header_and_payload(&self) -> Result<(String, String), Error> {
-        let jws_header = JwsHeader::new_v2(self.kid, &self.headers, self.jws_jku);
+        let jws_header = JwsHeader::new_v2(&self.kid, &self.headers, self.jws_jku.as_deref());
         let jws_header_b64 = serde_json::to_string(&jws_header)
-            .map_err(|e| Error::JwsError(e.into()))?
+            .map_err(|e| Error::SigningFailed(e.into()))?
             .to_url_safe_base64();
 
-        let signing_payload =
-            build_v2_signing_payload(self.method, self.path, &self.headers, self.body, false);
+        Ok((jws_header_b64, self.signing_payload().to_url_safe_base64()))
+    }
 
-        Ok((jws_header_b64, signing_payload.to_url_safe_base64()))
+    /// The v2 signing payload, before base64 encoding, e.g. to debug signature mismatches.
+    ///
+    /// # Example
+    /// ```txt
+    /// POST /payouts
+    /// Idempotency-Key: 619410b3-b00c-406e-bb1b-2982f97edb8b
+    /// {"bar":123}
+    /// ```
+    pub fn signing_payload(&self) -> Vec<u8> {
+        build_v2_signing_payload(self.method, &self.path, &self.headers, &self.body, false)
     }
 
     /// Produce a JWS `Tl-Signature` v2 header value with a custom signer.
@@ -66,4 +79,37 @@ impl<'a> CustomSigner<'a> {
         let signature = sign_fn(sig_payload.as_bytes()).await?;
         Ok(format!("{}..{}", jws_header, signature))
     }
+
+    /// Produce a JWS `Tl-Signature` v2 header value using an [`Es512Signer`].
+    ///
+    /// The raw signature is validated & normalised to the JWS encoding.
+    pub fn sign_with_signer(self, signer: &impl Es512Signer) -> Result<String, Error> {
+        self.sign_with(|bytes| signer.sign_es512(bytes)?.to_jws_signature())
+    }
+
+    /// Produce a JWS `Tl-Signature` v2 header value using an [`AsyncEs512Signer`].
+    ///
+    /// The raw signature is validated & normalised to the JWS encoding.
+    pub async fn async_sign_with_signer(
+        self,
+        signer: &impl AsyncEs512Signer,
+    ) -> Result<String, Error> {
+        let (jws_header, payload) = self.build_jws_header_and_payload()?;
+        let sig_payload = format!("{}.{}", jws_header, payload);
+        let signature = signer.sign_es512(sig_payload.as_bytes()).await?;
+        Ok(format!("{}..{}", jws_header, signature.to_jws_signature()?))
+    }
+
+    /// Converts into a `'static` signer owning all its data, copying any borrowed
+    /// values.
+    pub fn into_owned(self) -> CustomSigner<'static> {
+        CustomSigner {
+            kid: IntoOwned::into_owned(self.kid),
+            body: IntoOwned::into_owned(self.body),
+            method: self.method,
+            path: IntoOwned::into_owned(self.path),
+            headers: owned_headers(self.headers),
+            jws_jku: self.jws_jku.map(IntoOwned::into_owned),
+        }
+    }
 }
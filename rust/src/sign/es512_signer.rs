@@ -0,0 +1,146 @@
+use std::future::Future;
+
+use crate::{base64::ToUrlSafeBase64, crypto, BoxError, Error, SigningKey};
+
+/// P-521 `r` & `s` byte length.
+const SCALAR_LEN: usize = 66;
+
+/// A raw ES512 signature produced by an [`Es512Signer`] or [`AsyncEs512Signer`].
+///
+/// Either form is normalised to the fixed length 132-byte JWS encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Es512Signature {
+    /// ASN.1 DER `ECDSA-Sig-Value`, as returned by e.g. AWS KMS, GCP KMS & Vault.
+    Der(Vec<u8>),
+    /// Fixed length 132-byte `r || s`, each left-padded to 66 bytes.
+    Fixed(Vec<u8>),
+}
+
+impl Es512Signature {
+    /// Returns the url-safe base64 encoded 132-byte `r || s` JWS signature.
+    pub(crate) fn to_jws_signature(&self) -> Result<String, Error> {
+        let fixed = match self {
+            Self::Der(der) => der_to_fixed(der).map_err(Error::SigningFailed)?,
+            Self::Fixed(fixed) if fixed.len() == 2 * SCALAR_LEN => fixed.clone(),
+            Self::Fixed(_) => {
+                return Err(Error::SigningFailed(
+                    "unexpected ES512 signature length".into(),
+                ))
+            }
+        };
+        Ok(fixed.to_url_safe_base64())
+    }
+}
+
+/// Produces ES512 signatures, e.g. using a key held by a KMS or HSM.
+///
+/// Used with [`CustomSigner::sign_with_signer`](crate::CustomSigner::sign_with_signer).
+pub trait Es512Signer {
+    /// Sign `payload` using ECDSA P-521 with SHA-512.
+    ///
+    /// `payload` is the JWS signing input & must be SHA-512 hashed by the signer,
+    /// or before calling a signer that accepts a pre-computed digest.
+    fn sign_es512(&self, payload: &[u8]) -> Result<Es512Signature, Error>;
+}
+
+/// Asynchronously produces ES512 signatures, e.g. using a key held by a KMS or HSM.
+///
+/// Used with [`CustomSigner::async_sign_with_signer`](crate::CustomSigner::async_sign_with_signer).
+///
+/// Implementations may use an `async fn`.
+pub trait AsyncEs512Signer {
+    /// Sign `payload` using ECDSA P-521 with SHA-512.
+    ///
+    /// `payload` is the JWS signing input & must be SHA-512 hashed by the signer,
+    /// or before calling a signer that accepts a pre-computed digest.
+    fn sign_es512(
+        &self,
+        payload: &[u8],
+    ) -> impl Future<Output = Result<Es512Signature, Error>> + Send;
+}
+
+/// Local software signer, e.g. for testing code written against the signer traits.
+impl Es512Signer for SigningKey {
+    fn sign_es512(&self, payload: &[u8]) -> Result<Es512Signature, Error> {
+        crypto::sign_es512(&self.key, payload).map(Es512Signature::Fixed)
+    }
+}
+
+/// Local software signer, e.g. for testing code written against the signer traits.
+impl AsyncEs512Signer for SigningKey {
+    async fn sign_es512(&self, payload: &[u8]) -> Result<Es512Signature, Error> {
+        Es512Signer::sign_es512(self, payload)
+    }
+}
+
+/// Convert a DER `ECDSA-Sig-Value` `SEQUENCE { r INTEGER, s INTEGER }`
+/// to fixed length `r || s`.
+fn der_to_fixed(der: &[u8]) -> Result<Vec<u8>, BoxError> {
+    let (sequence, rest) = der_element(der, 0x30)?;
+    if !rest.is_empty() {
+        return Err("trailing data after DER signature".into());
+    }
+    let (r, sequence) = der_element(sequence, 0x02)?;
+    let (s, sequence) = der_element(sequence, 0x02)?;
+    if !sequence.is_empty() {
+        return Err("unexpected data in DER signature".into());
+    }
+
+    let mut fixed = vec![0; 2 * SCALAR_LEN];
+    for (int, out) in [r, s].into_iter().zip(fixed.chunks_mut(SCALAR_LEN)) {
+        let leading_zeros = int.iter().take_while(|b| **b == 0).count();
+        let int = &int[leading_zeros..];
+        if int.len() > SCALAR_LEN {
+            return Err("DER signature integer too long for P-521".into());
+        }
+        out[SCALAR_LEN - int.len()..].copy_from_slice(int);
+    }
+    Ok(fixed)
+}
+
+/// Read a DER element with the given `tag`, returning the contents & remaining input.
+fn der_element(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), BoxError> {
+    let (&actual_tag, input) = input.split_first().ok_or("truncated DER signature")?;
+    if actual_tag != tag {
+        return Err("unexpected DER signature tag".into());
+    }
+    let (&len, input) = input.split_first().ok_or("truncated DER signature")?;
+    // a P-521 signature is at most 139 bytes so at most 1 extra length byte
+    let (len, input) = match len {
+        0..=0x7f => (len as usize, input),
+        0x81 => {
+            let (&len, input) = input.split_first().ok_or("truncated DER signature")?;
+            (len as usize, input)
+        }
+        _ => return Err("unsupported DER signature length".into()),
+    };
+    if input.len() < len {
+        return Err("truncated DER signature".into());
+    }
+    Ok(input.split_at(len))
+}
+
+#[test]
+fn der_to_fixed_pads() {
+    // r = 1, s = 0x80 (with sign padding byte)
+    let der = [0x30, 0x07, 0x02, 0x01, 0x01, 0x02, 0x02, 0x00, 0x80];
+    let fixed = der_to_fixed(&der).unwrap();
+    assert_eq!(fixed.len(), 132);
+    assert_eq!(fixed[65], 0x01);
+    assert_eq!(fixed[131], 0x80);
+    assert!(fixed[..65].iter().all(|b| *b == 0));
+    assert!(fixed[66..131].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn der_to_fixed_invalid() {
+    // truncated
+    assert!(der_to_fixed(&[0x30, 0x07, 0x02, 0x01, 0x01]).is_err());
+    // not a sequence
+    assert!(der_to_fixed(&[0x02, 0x01, 0x01]).is_err());
+    // 67 byte r
+    let mut der = vec![0x30, 0x81, 0x48, 0x02, 0x43];
+    der.extend([0x01; 67]);
+    der.extend([0x02, 0x01, 0x01]);
+    assert!(der_to_fixed(&der).is_err());
+}
@@ -1,13 +1,21 @@
 mod custom_signer;
+mod es512_signer;
+mod request_signer;
 mod signer_v1;
+mod signing_key;
 
 use indexmap::IndexMap;
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use crate::{base64::ToUrlSafeBase64, http::HeaderName, openssl, Error, Method, Unset};
+use crate::{
+    base64::ToUrlSafeBase64, crypto, http::HeaderName, owned::IntoOwned, Error, Method, Unset,
+};
 
 pub use self::custom_signer::CustomSigner;
+pub use self::es512_signer::{AsyncEs512Signer, Es512Signature, Es512Signer};
+pub use self::request_signer::RequestSigner;
 use self::signer_v1::SignerV1;
+pub use self::signing_key::SigningKey;
 
 /// Builder to generate a `Tl-Signature` header value.
 ///
@@ -24,15 +32,16 @@ use self::signer_v1::SignerV1;
 ///     .sign()?;
 /// # Ok(()) }
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SignerBuilder<'a, Kid, Pk, Body, Method, Path> {
     kid: Kid,
     private_key: Pk,
     body: Body,
     method: Method,
     path: Path,
-    headers: IndexMap<HeaderName<'a>, &'a [u8]>,
-    jws_jku: Option<&'a str>,
+    headers: IndexMap<HeaderName<'a>, Cow<'a, [u8]>>,
+    jws_jku: Option<Cow<'a, str>>,
+    passphrase: Option<Cow<'a, [u8]>>,
 }
 
 impl<Kid, Pk, Body, Method, Path> fmt::Debug for SignerBuilder<'_, Kid, Pk, Body, Method, Path> {
@@ -51,35 +60,54 @@ impl<'a> SignerBuilder<'a, Unset, Unset, Unset, Unset, Unset> {
             path: Unset,
             headers: <_>::default(),
             jws_jku: <_>::default(),
+            passphrase: None,
         }
     }
 }
 
-impl<'a> SignerBuilder<'a, &'a str, &'a [u8], Unset, Unset, Unset> {
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, [u8]>, Unset, Unset, Unset> {
     pub fn build_with_pem(kid: &'a str, private_key: &'a [u8]) -> Self {
         SignerBuilder {
-            kid,
-            private_key,
+            kid: Cow::Borrowed(kid),
+            private_key: Cow::Borrowed(private_key),
             body: Unset,
             method: Unset,
             path: Unset,
             headers: <_>::default(),
             jws_jku: <_>::default(),
+            passphrase: None,
+        }
+    }
+}
+
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, SigningKey>, Unset, Unset, Unset> {
+    /// Start building with a pre-parsed [`SigningKey`], the kid is taken from the key.
+    pub fn build_with_key(signing_key: &'a SigningKey) -> Self {
+        SignerBuilder {
+            kid: Cow::Borrowed(signing_key.kid()),
+            private_key: Cow::Borrowed(signing_key),
+            body: Unset,
+            method: Unset,
+            path: Unset,
+            headers: <_>::default(),
+            jws_jku: <_>::default(),
+            passphrase: None,
         }
     }
 }
 
 impl<'a, Pk, Body, Method, Path> SignerBuilder<'a, Unset, Pk, Body, Method, Path> {
     /// Add the private key kid.
-    pub fn kid(self, kid: &'a str) -> SignerBuilder<'a, &'a str, Pk, Body, Method, Path> {
+    pub fn kid(self, kid: &'a str) -> SignerBuilder<'a, Cow<'a, str>, Pk, Body, Method, Path> {
         SignerBuilder {
-            kid,
+            kid: Cow::Borrowed(kid),
             private_key: self.private_key,
             body: self.body,
             method: self.method,
             path: self.path,
             headers: self.headers,
             jws_jku: self.jws_jku,
+            passphrase: self.passphrase,
         }
     }
 }
@@ -88,16 +116,17 @@ impl<'a, K, Body, Method, Path> SignerBuilder<'a, K, Unset, Body, Method, Path>
     /// Add the private key.
     pub fn private_key(
         self,
-        private_key: &[u8],
-    ) -> SignerBuilder<'a, K, &[u8], Body, Method, Path> {
+        private_key: &'a [u8],
+    ) -> SignerBuilder<'a, K, Cow<'a, [u8]>, Body, Method, Path> {
         SignerBuilder {
             kid: self.kid,
-            private_key,
+            private_key: Cow::Borrowed(private_key),
             body: self.body,
             method: self.method,
             path: self.path,
             headers: self.headers,
             jws_jku: self.jws_jku,
+            passphrase: self.passphrase,
         }
     }
 }
@@ -106,15 +135,16 @@ impl<'a, K, Pk, Method, Path> SignerBuilder<'a, K, Pk, Unset, Method, Path> {
     /// Add the full request body.
     ///
     /// Note: This **must** be identical to what is sent with the request.
-    pub fn body(self, body: &[u8]) -> SignerBuilder<'a, K, Pk, &[u8], Method, Path> {
+    pub fn body(self, body: &'a [u8]) -> SignerBuilder<'a, K, Pk, Cow<'a, [u8]>, Method, Path> {
         SignerBuilder {
             kid: self.kid,
             private_key: self.private_key,
-            body,
+            body: Cow::Borrowed(body),
             method: self.method,
             path: self.path,
             headers: self.headers,
             jws_jku: self.jws_jku,
+            passphrase: self.passphrase,
         }
     }
 }
@@ -130,6 +160,7 @@ impl<'a, K, Pk, Body, Path> SignerBuilder<'a, K, Pk, Body, Unset, Path> {
             path: self.path,
             headers: self.headers,
             jws_jku: self.jws_jku,
+            passphrase: self.passphrase,
         }
     }
 }
@@ -137,7 +168,7 @@ impl<'a, K, Pk, Body, Path> SignerBuilder<'a, K, Pk, Body, Unset, Path> {
 impl<'a, K, Pk, Body, Method> SignerBuilder<'a, K, Pk, Body, Method, Unset> {
     /// Add the request absolute path starting with a leading `/` and without
     /// any trailing slashes.
-    pub fn path(self, path: &str) -> SignerBuilder<'a, K, Pk, Body, Method, &str> {
+    pub fn path(self, path: &'a str) -> SignerBuilder<'a, K, Pk, Body, Method, Cow<'a, str>> {
         assert!(
             path.starts_with('/'),
             "Invalid path \"{path}\" must start with '/'"
@@ -147,9 +178,10 @@ impl<'a, K, Pk, Body, Method> SignerBuilder<'a, K, Pk, Body, Method, Unset> {
             private_key: self.private_key,
             body: self.body,
             method: self.method,
-            path,
+            path: Cow::Borrowed(path),
             headers: self.headers,
             jws_jku: self.jws_jku,
+            passphrase: self.passphrase,
         }
     }
 }
@@ -160,7 +192,8 @@ impl<'a, K, Pk, Body, Method, Path> SignerBuilder<'a, K, Pk, Body, Method, Path>
     ///
     /// Warning: Only a single value per header name is supported.
     pub fn header(mut self, key: &'a str, value: &'a [u8]) -> Self {
-        self.headers.insert(HeaderName(key), value);
+        self.headers
+            .insert(HeaderName::new(key), Cow::Borrowed(value));
         self
     }
 
@@ -168,8 +201,11 @@ impl<'a, K, Pk, Body, Method, Path> SignerBuilder<'a, K, Pk, Body, Method, Path>
     ///
     /// Warning: Only a single value per header name is supported.
     pub fn headers(mut self, headers: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Self {
-        self.headers
-            .extend(headers.into_iter().map(|(k, v)| (HeaderName(k), v)));
+        self.headers.extend(
+            headers
+                .into_iter()
+                .map(|(k, v)| (HeaderName::new(k), Cow::Borrowed(v))),
+        );
         self
     }
 
@@ -178,12 +214,74 @@ impl<'a, K, Pk, Body, Method, Path> SignerBuilder<'a, K, Pk, Body, Method, Path>
     /// Note: This is not generally required when calling APIs,
     /// but is set on webhook signatures.
     pub fn jku(mut self, jku: &'a str) -> Self {
-        self.jws_jku = Some(jku);
+        self.jws_jku = Some(Cow::Borrowed(jku));
+        self
+    }
+}
+
+impl<Kid, Pk, Body, Method, Path> SignerBuilder<'_, Kid, Pk, Body, Method, Path>
+where
+    Kid: IntoOwned,
+    Pk: IntoOwned,
+    Body: IntoOwned,
+    Method: IntoOwned,
+    Path: IntoOwned,
+{
+    /// Converts into a `'static` builder owning all its data, copying any borrowed
+    /// values.
+    ///
+    /// This allows a partially configured builder to be stored, e.g. in middleware,
+    /// cloned for each request & sent across threads. Signatures are unaffected.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), truelayer_signing::Error> {
+    /// # let (kid, private_key): (String, Vec<u8>) = unimplemented!();
+    /// let signer = truelayer_signing::sign_with_pem(&kid, &private_key)
+    ///     .method(truelayer_signing::Method::Post)
+    ///     .path("/payouts")
+    ///     .into_owned();
+    ///
+    /// std::thread::spawn(move || {
+    ///     # let (idempotency_key, body): (Vec<u8>, Vec<u8>) = unimplemented!();
+    ///     let tl_signature = signer
+    ///         .header("Idempotency-Key", &idempotency_key)
+    ///         .body(&body)
+    ///         .build_signer()
+    ///         .sign();
+    /// });
+    /// # Ok(()) }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn into_owned(
+        self,
+    ) -> SignerBuilder<'static, Kid::Owned, Pk::Owned, Body::Owned, Method::Owned, Path::Owned>
+    {
+        SignerBuilder {
+            kid: self.kid.into_owned(),
+            private_key: self.private_key.into_owned(),
+            body: self.body.into_owned(),
+            method: self.method.into_owned(),
+            path: self.path.into_owned(),
+            headers: owned_headers(self.headers),
+            jws_jku: self.jws_jku.map(IntoOwned::into_owned),
+            passphrase: self.passphrase.map(IntoOwned::into_owned),
+        }
+    }
+}
+
+impl<'a, K, Body, Method, Path> SignerBuilder<'a, K, Cow<'a, [u8]>, Body, Method, Path> {
+    /// Sets the passphrase to decrypt a PKCS#8 encrypted (`ENCRYPTED PRIVATE KEY`)
+    /// private key pem.
+    ///
+    /// Signing returns [`Error::IncorrectPassphrase`] if the key cannot be decrypted.
+    pub fn passphrase(mut self, passphrase: &'a [u8]) -> Self {
+        self.passphrase = Some(Cow::Borrowed(passphrase));
         self
     }
 }
 
-impl<'a> SignerBuilder<'a, &'a str, Unset, &'a [u8], Method, &'a str> {
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Unset, Cow<'a, [u8]>, Method, Cow<'a, str>> {
     /// Builds a [`CustomSigner`]
     ///
     /// requires the kid, body, method, and path to be set to call this function.
@@ -200,7 +298,7 @@ impl<'a> SignerBuilder<'a, &'a str, Unset, &'a [u8], Method, &'a str> {
     }
 }
 
-impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Unset, Unset> {
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, [u8]>, Cow<'a, [u8]>, Unset, Unset> {
     /// Build a V1 Signer see [`SignerV1`].
     ///
     /// requires the private key, kid, and body to be set to call this function.
@@ -209,7 +307,27 @@ impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Unset, Unset> {
     /// In general full request signing should be preferred, see [`Signer`].
     pub fn build_v1_signer(self) -> SignerV1<'a> {
         SignerV1 {
-            private_key: self.private_key,
+            private_key: PrivateKey::Pem {
+                pem: self.private_key,
+                passphrase: self.passphrase,
+            },
+            kid: self.kid,
+            body: self.body,
+            jws_jku: self.jws_jku,
+        }
+    }
+}
+
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, SigningKey>, Cow<'a, [u8]>, Unset, Unset> {
+    /// Build a V1 Signer see [`SignerV1`].
+    ///
+    /// requires the signing key and body to be set to call this function.
+    /// if the method of path is set this function will not be available.
+    ///
+    /// In general full request signing should be preferred, see [`Signer`].
+    pub fn build_v1_signer(self) -> SignerV1<'a> {
+        SignerV1 {
+            private_key: PrivateKey::Key(self.private_key),
             kid: self.kid,
             body: self.body,
             jws_jku: self.jws_jku,
@@ -217,13 +335,35 @@ impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Unset, Unset> {
     }
 }
 
-impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Method, &'a str> {
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, [u8]>, Cow<'a, [u8]>, Method, Cow<'a, str>> {
     /// Build a V2 Signer see [`Signer`].
     ///
     /// requires the private key, kid, body, method, and path to be set to call this function.
     pub fn build_signer(self) -> Signer<'a> {
         Signer {
-            private_key: self.private_key,
+            private_key: PrivateKey::Pem {
+                pem: self.private_key,
+                passphrase: self.passphrase,
+            },
+            base: CustomSigner {
+                kid: self.kid,
+                body: self.body,
+                method: self.method.name(),
+                path: self.path,
+                headers: self.headers,
+                jws_jku: self.jws_jku,
+            },
+        }
+    }
+}
+
+impl<'a> SignerBuilder<'a, Cow<'a, str>, Cow<'a, SigningKey>, Cow<'a, [u8]>, Method, Cow<'a, str>> {
+    /// Build a V2 Signer see [`Signer`].
+    ///
+    /// requires the signing key, body, method, and path to be set to call this function.
+    pub fn build_signer(self) -> Signer<'a> {
+        Signer {
+            private_key: PrivateKey::Key(self.private_key),
             base: CustomSigner {
                 kid: self.kid,
                 body: self.body,
@@ -236,6 +376,46 @@ impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Method, &'a str> {
     }
 }
 
+/// Private key for signing.
+#[derive(Clone)]
+pub(crate) enum PrivateKey<'a> {
+    /// Private key PEM, parsed & decrypted with any passphrase when signing.
+    Pem {
+        pem: Cow<'a, [u8]>,
+        passphrase: Option<Cow<'a, [u8]>>,
+    },
+    /// Pre-parsed signing key.
+    Key(Cow<'a, SigningKey>),
+}
+
+impl PrivateKey<'_> {
+    /// Sign `payload` returning the url-safe base64 encoded ES512 signature.
+    pub(crate) fn sign_es512(&self, payload: &[u8]) -> Result<String, Error> {
+        let parsed;
+        let private_key = match self {
+            PrivateKey::Pem { pem, passphrase } => {
+                parsed = match passphrase {
+                    Some(passphrase) => crypto::parse_ec_private_key_encrypted(pem, passphrase)?,
+                    None => crypto::parse_ec_private_key(pem)?,
+                };
+                &parsed
+            }
+            PrivateKey::Key(key) => &key.key,
+        };
+        crypto::sign_es512(private_key, payload).map(|sig| sig.to_url_safe_base64())
+    }
+
+    fn into_owned(self) -> PrivateKey<'static> {
+        match self {
+            PrivateKey::Pem { pem, passphrase } => PrivateKey::Pem {
+                pem: IntoOwned::into_owned(pem),
+                passphrase: passphrase.map(IntoOwned::into_owned),
+            },
+            PrivateKey::Key(key) => PrivateKey::Key(IntoOwned::into_owned(key)),
+        }
+    }
+}
+
 /// Signer to generate a `Tl-Signature` header value using a private key.
 ///
 /// # Example
@@ -251,9 +431,10 @@ impl<'a> SignerBuilder<'a, &'a str, &'a [u8], &'a [u8], Method, &'a str> {
 ///     .sign()?;
 /// # Ok(()) }
 /// ```
+#[derive(Clone)]
 pub struct Signer<'a> {
     base: CustomSigner<'a>,
-    private_key: &'a [u8],
+    private_key: PrivateKey<'a>,
 }
 
 /// Debug does not display key info.
@@ -266,14 +447,28 @@ impl fmt::Debug for Signer<'_> {
 impl<'a> Signer<'a> {
     /// Produce a JWS `Tl-Signature` v2 header value.
     pub fn sign(self) -> Result<String, Error> {
-        let private_key =
-            openssl::parse_ec_private_key(self.private_key).map_err(Error::InvalidKey)?;
-        self.base.sign_with(|bytes| {
-            openssl::sign_es512(&private_key, bytes)
-                .map(|sig| sig.to_url_safe_base64())
-                .map_err(Error::JwsError)
-        })
+        let private_key = self.private_key;
+        self.base.sign_with(|bytes| private_key.sign_es512(bytes))
     }
+
+    /// Converts into a `'static` signer owning all its data, copying any borrowed
+    /// values.
+    pub fn into_owned(self) -> Signer<'static> {
+        Signer {
+            base: self.base.into_owned(),
+            private_key: self.private_key.into_owned(),
+        }
+    }
+}
+
+/// Copies any borrowed header names & values.
+pub(crate) fn owned_headers(
+    headers: IndexMap<HeaderName<'_>, Cow<'_, [u8]>>,
+) -> IndexMap<HeaderName<'static>, Cow<'static, [u8]>> {
+    headers
+        .into_iter()
+        .map(|(k, v)| (k.into_owned(), IntoOwned::into_owned(v)))
+        .collect()
 }
 
 /// Build a v2 signing payload.
@@ -287,7 +482,7 @@ impl<'a> Signer<'a> {
 pub(crate) fn build_v2_signing_payload(
     method: &str,
     path: &str,
-    headers: &IndexMap<HeaderName<'_>, &[u8]>,
+    headers: &IndexMap<HeaderName<'_>, impl AsRef<[u8]>>,
     body: &[u8],
     add_path_trailing_slash: bool,
 ) -> Vec<u8> {
@@ -300,9 +495,9 @@ pub(crate) fn build_v2_signing_payload(
     }
     payload.push(b'\n');
     for (h_name, h_val) in headers {
-        payload.extend(h_name.0.as_bytes());
+        payload.extend(h_name.as_str().as_bytes());
         payload.extend(b": ");
-        payload.extend(*h_val);
+        payload.extend(h_val.as_ref());
         payload.push(b'\n');
     }
     payload.extend(body);
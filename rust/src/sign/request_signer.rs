@@ -0,0 +1,104 @@
+use indexmap::IndexMap;
+
+use crate::{http::HeaderName, Error, Method, SigningKey};
+
+const DEFAULT_SIGNED_HEADERS: [&str; 1] = ["Idempotency-Key"];
+
+/// Signs many requests using one configuration, e.g. held by a long-lived http client
+/// & shared across threads.
+///
+/// Each signature includes the request method, path, body & the configured signed headers
+/// present in the request.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (kid, private_key, idempotency_key, body): (&str, &[u8], &[u8], &[u8]) = unimplemented!();
+/// let signing_key = truelayer_signing::SigningKey::from_pem(kid, private_key)?;
+/// let signer = truelayer_signing::RequestSigner::new(signing_key);
+///
+/// let tl_signature = signer.sign(
+///     truelayer_signing::Method::Post,
+///     "/payouts",
+///     [("Idempotency-Key", idempotency_key)],
+///     body,
+/// )?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestSigner {
+    signing_key: SigningKey,
+    jku: Option<String>,
+    signed_headers: Vec<String>,
+}
+
+impl RequestSigner {
+    /// Create a signer using the given key & its `kid`, including the
+    /// `Idempotency-Key` header.
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self {
+            signing_key,
+            jku: None,
+            signed_headers: DEFAULT_SIGNED_HEADERS.map(String::from).to_vec(),
+        }
+    }
+
+    /// Sets the jws header `jku` JSON Web Key URL.
+    ///
+    /// Note: This is not generally required when calling APIs,
+    /// but is set on webhook signatures.
+    pub fn jku(mut self, jku: impl Into<String>) -> Self {
+        self.jku = Some(jku.into());
+        self
+    }
+
+    /// Replace the headers to include in signatures, default `Idempotency-Key`.
+    ///
+    /// Headers not present in a request are not signed.
+    pub fn signed_headers(mut self, headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.signed_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The key used to sign requests.
+    pub fn signing_key(&self) -> &SigningKey {
+        &self.signing_key
+    }
+
+    /// Produce a JWS `Tl-Signature` v2 header value for a request.
+    ///
+    /// All request `headers` may be passed, only the configured signed headers are
+    /// included in the signature, matched case-insensitively. If a header is repeated
+    /// the first value is used.
+    ///
+    /// # Panics
+    /// If `path` does not start with a '/' char.
+    pub fn sign<'h>(
+        &self,
+        method: Method,
+        path: &str,
+        headers: impl IntoIterator<Item = (&'h str, &'h [u8])>,
+        body: &[u8],
+    ) -> Result<String, Error> {
+        let mut request_headers = IndexMap::new();
+        for (name, value) in headers {
+            request_headers
+                .entry(HeaderName::new(name))
+                .or_insert(value);
+        }
+        let signed_headers = self.signed_headers.iter().filter_map(|name| {
+            let value = request_headers.get(&HeaderName::new(name))?;
+            Some((name.as_str(), *value))
+        });
+
+        let mut signer = crate::sign_with_key(&self.signing_key)
+            .method(method)
+            .path(path)
+            .headers(signed_headers)
+            .body(body);
+        if let Some(jku) = &self.jku {
+            signer = signer.jku(jku);
+        }
+        signer.build_signer().sign()
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{base64::ToUrlSafeBase64, openssl, Error};
+use std::borrow::Cow;
+
+use crate::{base64::ToUrlSafeBase64, Error};
+
+use super::PrivateKey;
 
 /// Produce a JWS `Tl-Signature` v1 header value, signing just the request body.
 ///
@@ -6,10 +10,10 @@ use crate::{base64::ToUrlSafeBase64, openssl, Error};
 ///
 /// In general full request signing should be preferred, see [`Signer::sign`].
 pub struct SignerV1<'a> {
-    pub(crate) private_key: &'a [u8],
-    pub(crate) kid: &'a str,
-    pub(crate) body: &'a [u8],
-    pub(crate) jws_jku: Option<&'a str>,
+    pub(crate) private_key: PrivateKey<'a>,
+    pub(crate) kid: Cow<'a, str>,
+    pub(crate) body: Cow<'a, [u8]>,
+    pub(crate) jws_jku: Option<Cow<'a, str>>,
 }
 
 impl<'a> SignerV1<'a> {
@@ -19,9 +23,6 @@ impl<'a> SignerV1<'a> {
     ///
     /// In general full request signing should be preferred, see [`Signer::sign`].
     pub fn sign_body_only(self) -> Result<String, Error> {
-        let private_key =
-            openssl::parse_ec_private_key(self.private_key).map_err(Error::InvalidKey)?;
-
         let jws_header = {
             let mut header = serde_json::json!({
                 "alg": "ES512",
@@ -31,14 +32,14 @@ impl<'a> SignerV1<'a> {
                 header["jku"] = jku.into();
             }
             serde_json::to_string(&header)
-                .map_err(|e| Error::JwsError(e.into()))?
+                .map_err(|e| Error::SigningFailed(e.into()))?
                 .to_url_safe_base64()
         };
         let jws_header_and_payload = format!("{}.{}", jws_header, self.body.to_url_safe_base64());
 
-        let signature = openssl::sign_es512(&private_key, jws_header_and_payload.as_bytes())
-            .map_err(Error::JwsError)?
-            .to_url_safe_base64();
+        let signature = self
+            .private_key
+            .sign_es512(jws_header_and_payload.as_bytes())?;
 
         let mut jws = jws_header;
         jws.push_str("..");
@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::{crypto, Error, VerifyingKey};
+
+/// A parsed P-521 private key & its `kid`.
+///
+/// Parsing & checking a private key is relatively expensive, a `SigningKey`
+/// does this once so it can be reused to sign many requests.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (kid, private_key, idempotency_key, body): (&str, _, _, _) = unimplemented!();
+/// let signing_key = truelayer_signing::SigningKey::from_pem(kid, private_key)?;
+///
+/// let tl_signature = truelayer_signing::sign_with_key(&signing_key)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/payouts")
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .build_signer()
+///     .sign()?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct SigningKey {
+    kid: String,
+    pub(crate) key: crypto::EcPrivateKey,
+}
+
+/// Debug does not display key info.
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SigningKey")
+            .field("kid", &self.kid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKey {
+    /// Parse a P-521 private key pem & associate it with the given `kid`.
+    pub fn from_pem(kid: impl Into<String>, private_key_pem: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_private_key(private_key_pem)?,
+        })
+    }
+
+    /// Parse a DER SEC1 P-521 private key & associate it with the given `kid`.
+    pub fn from_sec1_der(kid: impl Into<String>, private_key_der: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_private_key_sec1_der(private_key_der)?,
+        })
+    }
+
+    /// Parse a DER PKCS#8 P-521 private key & associate it with the given `kid`.
+    pub fn from_pkcs8_der(kid: impl Into<String>, private_key_der: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_private_key_pkcs8_der(private_key_der)?,
+        })
+    }
+
+    /// Parse a P-521 private JWK json, including the `d` parameter, using the JWK `kid`.
+    ///
+    /// The JWK `x` & `y` must match the public key of `d`.
+    pub fn from_jwk(jwk: &[u8]) -> Result<Self, Error> {
+        let (kid, key) = crypto::parse_ec_private_jwk(jwk)?;
+        Ok(Self { kid, key })
+    }
+
+    /// Parse a PKCS#8 encrypted (`ENCRYPTED PRIVATE KEY`) P-521 private key pem
+    /// using `passphrase` & associate it with the given `kid`.
+    ///
+    /// Returns [`Error::IncorrectPassphrase`] if the key cannot be decrypted.
+    pub fn from_encrypted_pem(
+        kid: impl Into<String>,
+        private_key_pem: &[u8],
+        passphrase: &[u8],
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_private_key_encrypted(private_key_pem, passphrase)?,
+        })
+    }
+
+    /// Generate a new random P-521 private key with a random uuid `kid`.
+    ///
+    /// See [`generate_key_pair`](crate::generate_key_pair).
+    pub fn generate() -> Result<Self, Error> {
+        Ok(Self {
+            kid: crypto::random_kid().map_err(Error::KeyGeneration)?,
+            key: crypto::generate_ec_private_key().map_err(Error::KeyGeneration)?,
+        })
+    }
+
+    /// The signing key id.
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// The public key with the same `kid`.
+    pub fn verifying_key(&self) -> Result<VerifyingKey, Error> {
+        let key = crypto::ec_public_key(&self.key).map_err(Error::InvalidKey)?;
+        Ok(VerifyingKey::new(self.kid.clone(), key))
+    }
+
+    /// Export the private key as a PKCS#8 `PRIVATE KEY` pem.
+    pub fn to_pkcs8_pem(&self) -> Result<String, Error> {
+        crypto::private_key_to_pkcs8_pem(&self.key).map_err(Error::InvalidKey)
+    }
+
+    /// Export the private key as a SEC1 `EC PRIVATE KEY` pem.
+    pub fn to_sec1_pem(&self) -> Result<String, Error> {
+        crypto::private_key_to_sec1_pem(&self.key).map_err(Error::InvalidKey)
+    }
+}
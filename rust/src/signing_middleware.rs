@@ -0,0 +1,95 @@
+use ::http::{header::HeaderValue, Extensions};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+
+use crate::{Error, Method, RequestSigner, SigningKey};
+
+/// [`reqwest_middleware::Middleware`] that signs outgoing requests, attaching
+/// the `Tl-Signature` header.
+///
+/// Each `POST`, `PUT`, `PATCH` & `DELETE` request is signed using the
+/// finalized request's method, path, body & signed headers. Requests with
+/// other methods, e.g. `GET`, are sent unsigned.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (kid, private_key): (&str, &[u8]) = unimplemented!();
+/// let signing_key = truelayer_signing::SigningKey::from_pem(kid, private_key)?;
+///
+/// let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+///     .with(truelayer_signing::SigningMiddleware::new(signing_key))
+///     .build();
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SigningMiddleware {
+    signer: RequestSigner,
+}
+
+impl SigningMiddleware {
+    /// Create a middleware signing requests with the given key, including
+    /// the `Idempotency-Key` header.
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self::from(RequestSigner::new(signing_key))
+    }
+
+    /// Replace the headers to include in signatures, default `Idempotency-Key`.
+    ///
+    /// Headers not present in a request are not signed.
+    pub fn signed_headers(mut self, headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.signer = self.signer.signed_headers(headers);
+        self
+    }
+
+    /// Sign the request, returns `Ok(None)` if the request should be sent unsigned.
+    fn tl_signature(&self, request: &Request) -> Result<Option<HeaderValue>, Error> {
+        let method = match Method::try_from(request.method()) {
+            Ok(Method::Get) | Err(_) => return Ok(None),
+            Ok(method) => method,
+        };
+        let body = match request.body() {
+            None => &[][..],
+            Some(body) => body.as_bytes().ok_or_else(|| {
+                Error::SigningFailed("streaming request bodies cannot be signed".into())
+            })?,
+        };
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes()));
+
+        let tl_signature = self
+            .signer
+            .sign(method, request.url().path(), headers, body)?;
+
+        HeaderValue::try_from(tl_signature)
+            .map(Some)
+            .map_err(|e| Error::SigningFailed(e.into()))
+    }
+}
+
+/// Sign requests using the [`RequestSigner`] configuration, e.g. including a `jku`.
+impl From<RequestSigner> for SigningMiddleware {
+    fn from(signer: RequestSigner) -> Self {
+        Self { signer }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for SigningMiddleware {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if let Some(tl_signature) = self
+            .tl_signature(&request)
+            .map_err(reqwest_middleware::Error::middleware)?
+        {
+            request.headers_mut().insert("Tl-Signature", tl_signature);
+        }
+        next.run(request, extensions).await
+    }
+}
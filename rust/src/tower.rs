@@ -0,0 +1,179 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use ::http::{header::CONTENT_TYPE, HeaderValue, Method, Request, Response, StatusCode};
+use bytes::Bytes;
+use http_body::Body;
+use http_body_util::{BodyExt, Full, Limited};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    verify_config::{error_code, rejection_body, rejection_status, VerifyConfig},
+    Error,
+};
+
+/// Default maximum buffered request body size, 2 MiB.
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// [`Layer`] verifying the `Tl-Signature` of inbound requests, see [`VerifySignature`].
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let public_key: &[u8] = unimplemented!();
+/// let keys = truelayer_signing::VerifyingKeySet::new()
+///     .with_pem("45fc75cf-5649-4134-84b3-192c2c78e990", public_key)?;
+/// let config = truelayer_signing::VerifyConfig::new(keys).require_header("Idempotency-Key");
+///
+/// let layer = truelayer_signing::VerifySignatureLayer::new(config);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifySignatureLayer {
+    config: Arc<Config>,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    verify: VerifyConfig,
+    body_limit: usize,
+    unverified_methods: Vec<Method>,
+}
+
+impl VerifySignatureLayer {
+    /// Verify requests using the given keys & policy.
+    pub fn new(verify: VerifyConfig) -> Self {
+        Self {
+            config: Arc::new(Config {
+                verify,
+                body_limit: DEFAULT_BODY_LIMIT,
+                unverified_methods: vec![],
+            }),
+        }
+    }
+
+    /// Maximum request body size to buffer, larger requests are rejected
+    /// with `413 Payload Too Large`.
+    ///
+    /// Default 2 MiB.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        Arc::make_mut(&mut self.config).body_limit = limit;
+        self
+    }
+
+    /// Pass requests with these methods to the inner service without verification,
+    /// e.g. `OPTIONS` CORS preflight or `HEAD` requests. These requests have no
+    /// [`VerifiedSignature`](crate::VerifiedSignature) extension.
+    ///
+    /// Default none, requests with methods not supported by the signing scheme are
+    /// rejected with `401 Unauthorized` & error code `unsupported_method`.
+    pub fn unverified_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        Arc::make_mut(&mut self.config)
+            .unverified_methods
+            .extend(methods);
+        self
+    }
+}
+
+impl<S> Layer<S> for VerifySignatureLayer {
+    type Service = VerifySignature<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VerifySignature {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// [`Service`] verifying the `Tl-Signature` of inbound requests.
+///
+/// The request body is buffered & verified along with the method, path & headers.
+/// Verified requests are passed to the inner service with the buffered body as a
+/// [`Full<Bytes>`](Full) & the [`VerifiedSignature`](crate::VerifiedSignature) inserted
+/// into the request extensions.
+/// Requests failing verification are rejected with `401 Unauthorized`, or
+/// `502 Bad Gateway` if the jwks could not be fetched, or `503 Service Unavailable`
+/// if the replay guard failed, & a json body describing
+/// the failure with a stable `error` code, e.g.
+/// `{"error":"invalid_signature","reason":"signature verification failed"}`.
+#[derive(Debug, Clone)]
+pub struct VerifySignature<S> {
+    inner: S,
+    config: Arc<Config>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for VerifySignature<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Body + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<crate::BoxError>,
+    ResBody: From<String>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        // use the ready service, leaving a clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let body = match Limited::new(body, config.body_limit).collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(err) => {
+                    let (status, reason) = match err.is::<http_body_util::LengthLimitError>() {
+                        true => (StatusCode::PAYLOAD_TOO_LARGE, "body_too_large"),
+                        false => (StatusCode::BAD_REQUEST, "invalid_body"),
+                    };
+                    return Ok(rejection(status, reason, &err.to_string()));
+                }
+            };
+
+            if !config.unverified_methods.contains(&parts.method) {
+                match crate::verify_request_parts(&parts, &body, &config.verify).await {
+                    Ok(verified) => parts.extensions.insert(verified),
+                    Err(err) => return Ok(verify_rejection(&err)),
+                };
+            }
+
+            inner
+                .call(Request::from_parts(parts, Full::new(body)))
+                .await
+        })
+    }
+}
+
+/// Json rejection response for a verification failure.
+pub(crate) fn verify_rejection<B: From<String>>(err: &Error) -> Response<B> {
+    let status = StatusCode::from_u16(rejection_status(err)).unwrap_or(StatusCode::UNAUTHORIZED);
+    rejection(status, error_code(err), &err.reason())
+}
+
+/// Json rejection response.
+pub(crate) fn rejection<B: From<String>>(
+    status: StatusCode,
+    error: &str,
+    reason: &str,
+) -> Response<B> {
+    let mut response = Response::new(B::from(rejection_body(error, reason)));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
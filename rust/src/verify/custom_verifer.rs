@@ -1,22 +1,49 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use anyhow::anyhow;
 use indexmap::{IndexMap, IndexSet};
 
 use crate::{
-    base64::ToUrlSafeBase64, http::HeaderName, jws::TlVersion, sign::build_v2_signing_payload,
-    Error,
+    base64::ToUrlSafeBase64,
+    http::HeaderName,
+    jws::TlVersion,
+    owned::IntoOwned,
+    sign::{build_v2_signing_payload, owned_headers},
+    Error, JwsHeader,
 };
 
-use super::{parse_tl_signature, ParsedTlSignature};
+use super::{
+    owned_header_names, parse_tl_signature, ParsedTlSignature, ReplayCheck, VerifiedSignature,
+};
 
 /// A `Tl-Signature` Verifier for custom signature verification.
+#[derive(Clone)]
 pub struct CustomVerifier<'a> {
-    pub(crate) body: &'a [u8],
+    pub(crate) body: Cow<'a, [u8]>,
     pub(crate) method: &'static str,
-    pub(crate) path: &'a str,
-    pub(crate) headers: IndexMap<HeaderName<'a>, &'a [u8]>,
+    pub(crate) path: Cow<'a, str>,
+    pub(crate) headers: IndexMap<HeaderName<'a>, Cow<'a, [u8]>>,
     pub(crate) required_headers: IndexSet<HeaderName<'a>>,
+    pub(crate) replay_guard: Option<ReplayCheck<'a>>,
+    pub(crate) trailing_slash: TrailingSlash,
+}
+
+/// How a verifier treats a signature of the request path with, or without,
+/// a trailing slash.
+///
+/// There is no separate "reported" policy, [`TrailingSlash::Tolerant`] always reports
+/// a fallback match in [`VerifiedSignature::signed_path`], so callers may log or reject
+/// requests that only verified with the trailing slash added or removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// The signed path must match the request path exactly.
+    Strict,
+    /// Also accept a signature of the request path with a trailing slash added,
+    /// or removed, e.g. `/payouts/` for a `/payouts` request.
+    ///
+    /// When this fallback matched [`VerifiedSignature::signed_path`] is set to the
+    /// signed path, otherwise it is `None`.
+    #[default]
+    Tolerant,
 }
 
 /// Debug does not display key info.
@@ -31,18 +58,44 @@ impl<'a> CustomVerifier<'a> {
         self,
         tl_signature: &'a str,
         verify_fn: impl FnMut(&[u8], &[u8]) -> Result<(), Error>,
-    ) -> Result<(), Error> {
+    ) -> Result<VerifiedSignature, Error> {
         let parsed_tl_signature = parse_tl_signature(tl_signature)?;
         self.verify_parsed_with(parsed_tl_signature, verify_fn)
     }
 
+    /// Converts into a `'static` verifier owning all its data, copying any borrowed
+    /// values.
+    pub fn into_owned(self) -> CustomVerifier<'static> {
+        CustomVerifier {
+            body: IntoOwned::into_owned(self.body),
+            method: self.method,
+            path: IntoOwned::into_owned(self.path),
+            headers: owned_headers(self.headers),
+            required_headers: owned_header_names(self.required_headers),
+            replay_guard: self.replay_guard.map(ReplayCheck::into_owned),
+            trailing_slash: self.trailing_slash,
+        }
+    }
+
     pub(crate) fn verify_parsed_with(
-        self,
+        &self,
+        tl_signature: ParsedTlSignature<'a>,
+        verify_fn: impl FnMut(&[u8], &[u8]) -> Result<(), Error>,
+    ) -> Result<VerifiedSignature, Error> {
+        let (verified, replay_key) = self.verify_parsed_unrecorded(tl_signature, verify_fn)?;
+        self.record_replay(replay_key)?;
+        Ok(verified)
+    }
+
+    /// Verify without recording the request with the replay guard, returning the
+    /// replay key to later pass to [`CustomVerifier::record_replay`].
+    pub(crate) fn verify_parsed_unrecorded(
+        &self,
         tl_signature: ParsedTlSignature<'a>,
         mut verify_fn: impl FnMut(&[u8], &[u8]) -> Result<(), Error>,
-    ) -> Result<(), Error> {
+    ) -> Result<(VerifiedSignature, Option<Vec<u8>>), Error> {
         let ParsedTlSignature {
-            header: jws_header,
+            header: mut jws_header,
             header_b64,
             signature,
         } = tl_signature;
@@ -53,90 +106,147 @@ impl<'a> CustomVerifier<'a> {
             .tl_version
             .map(Ok)
             .or_else(|| {
-                let version_header_name = HeaderName("Tl-Signature-Version");
+                let version_header_name = HeaderName::new("Tl-Signature-Version");
+                let version = self.get_header_string_value_safe(&version_header_name);
                 required_headers.insert(version_header_name);
-                self.get_header_string_value_safe(&version_header_name)
-                    .map(|v| match v.as_str() {
-                        "2" => Ok(TlVersion::V2),
-                        v => Err(Error::JwsError(anyhow!(
-                            "unexpected header tl_version: {}",
-                            v
-                        ))),
-                    })
+                version.map(|v| match v.as_str() {
+                    "2" => Ok(TlVersion::V2),
+                    _ => Err(Error::UnsupportedVersion(v)),
+                })
             })
-            .ok_or(Error::JwsError(anyhow!("missing header tl_version")))??;
+            .ok_or_else(|| Error::malformed_signature("missing header tl_version"))??;
 
         if version != TlVersion::V2 {
-            return Err(Error::JwsError(anyhow!(
-                "expected header tl_version v2 found {:?}",
-                version
-            )));
+            return Err(Error::UnsupportedVersion(version.to_string()));
         }
 
         let included_header_names_csv = jws_header
             .tl_headers
+            .take()
             .or_else(|| {
-                let headers_header_name = HeaderName("Tl-Signature-Headers");
+                let headers_header_name = HeaderName::new("Tl-Signature-Headers");
+                let headers = self.get_header_string_value_safe(&headers_header_name);
                 required_headers.insert(headers_header_name);
-                self.get_header_string_value_safe(&headers_header_name)
+                headers
             })
-            .ok_or_else(|| Error::JwsError(anyhow!("missing header tl_headers")))?;
+            .ok_or_else(|| Error::malformed_signature("missing header tl_headers"))?;
         // check and order all included headers
-        let ordered_headers = &self
-            .get_included_headers(&included_header_names_csv)
-            .map_err(Error::JwsError)?;
+        let ordered_headers = &self.get_included_headers(&included_header_names_csv)?;
 
         // fail if signature is missing a required header
         if let Some(header) = required_headers
             .iter()
             .find(|h| !ordered_headers.contains_key(*h))
         {
-            return Err(Error::JwsError(anyhow!(
-                "signature is missing required header {}",
-                header
-            )));
+            return Err(Error::MissingRequiredHeader(header.to_string()));
         }
 
         // reconstruct the payload as it would have been signed
         let signing_payload =
-            build_v2_signing_payload(self.method, self.path, ordered_headers, self.body, false);
+            build_v2_signing_payload(self.method, &self.path, ordered_headers, &self.body, false);
         let payload = format!("{header_b64}.{}", signing_payload.to_url_safe_base64());
 
-        verify_fn(payload.as_bytes(), signature.as_slice()).or_else(|e| {
-            // try again with/without a trailing slash (#80)
-            let (path, slash) = match self.path {
-                p if p.ends_with('/') => (&p[..p.len() - 1], false),
-                p => (p, true),
-            };
-            let signing_payload =
-                build_v2_signing_payload(self.method, path, ordered_headers, self.body, slash);
-            let payload = format!("{header_b64}.{}", signing_payload.to_url_safe_base64());
-            // use original error if both fail
-            verify_fn(payload.as_bytes(), signature.as_slice()).map_err(|_| e)
-        })
+        let (signing_input, signed_path) = match verify_fn(payload.as_bytes(), signature.as_slice())
+        {
+            Ok(()) => (payload, None),
+            Err(e) if self.trailing_slash == TrailingSlash::Strict => return Err(e),
+            Err(e) => {
+                // try again with/without a trailing slash (#80)
+                let (path, slash) = match &*self.path {
+                    p if p.ends_with('/') => (&p[..p.len() - 1], false),
+                    p => (p, true),
+                };
+                let signing_payload =
+                    build_v2_signing_payload(self.method, path, ordered_headers, &self.body, slash);
+                let payload = format!("{header_b64}.{}", signing_payload.to_url_safe_base64());
+                // use original error if both fail
+                verify_fn(payload.as_bytes(), signature.as_slice()).map_err(|_| e)?;
+                let signed_path = if slash {
+                    format!("{path}/")
+                } else {
+                    path.to_owned()
+                };
+                (payload, Some(signed_path))
+            }
+        };
+
+        let replay_key = match &self.replay_guard {
+            Some(replay_guard) => {
+                Some(replay_guard.key(signing_input.as_bytes(), ordered_headers)?)
+            }
+            None => None,
+        };
+
+        let mut verified = VerifiedSignature::new(jws_header, version);
+        verified.signed_headers = ordered_headers.keys().map(|h| h.to_string()).collect();
+        verified.signed_path = signed_path;
+        Ok((verified, replay_key))
+    }
+
+    /// Record a verified request with the replay guard, failing if it is a replay.
+    pub(crate) fn record_replay(&self, replay_key: Option<Vec<u8>>) -> Result<(), Error> {
+        match (&self.replay_guard, replay_key) {
+            (Some(replay_guard), Some(key)) => replay_guard.record(&key),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a verified request with the, possibly async, replay guard, failing if it
+    /// is a replay.
+    pub(crate) async fn record_replay_async(
+        &self,
+        replay_key: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        match (&self.replay_guard, replay_key) {
+            (Some(replay_guard), Some(key)) => replay_guard.record_async(&key).await,
+            _ => Ok(()),
+        }
     }
 
-    fn get_included_headers(
+    /// Reconstruct the v2 signing payload of the request path for the given
+    /// jws header, as it would have been signed, with the given `body`.
+    pub(crate) fn signing_payload(
         &self,
-        included_header_names_csv: &'a str,
-    ) -> anyhow::Result<IndexMap<HeaderName<'a>, &'a [u8]>> {
+        jws_header: &JwsHeader<'_>,
+        body: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let included_header_names_csv = match &jws_header.tl_headers {
+            Some(headers) => headers.clone(),
+            None => self
+                .get_header_string_value_safe(&HeaderName::new("Tl-Signature-Headers"))
+                .ok_or_else(|| Error::malformed_signature("missing header tl_headers"))?,
+        };
+        let ordered_headers = self.get_included_headers(&included_header_names_csv)?;
+        Ok(build_v2_signing_payload(
+            self.method,
+            &self.path,
+            &ordered_headers,
+            body,
+            false,
+        ))
+    }
+
+    fn get_included_headers<'s>(
+        &'s self,
+        included_header_names_csv: &'s str,
+    ) -> Result<IndexMap<HeaderName<'s>, &'s [u8]>, Error> {
         let included_header_names: IndexSet<_> = included_header_names_csv
             .split(',')
             .filter(|h| !h.is_empty())
-            .map(HeaderName)
+            .map(HeaderName::new)
             .collect();
 
         // populate included headers in specified order
         let ordered_headers: IndexMap<_, _> = included_header_names
-            .iter()
+            .into_iter()
             .map(|h| {
                 let hval = self
                     .headers
-                    .get(h)
-                    .ok_or_else(|| anyhow!("Missing tl_header `{}` declared in signature", h))?;
-                Ok((*h, *hval))
+                    .get(&h)
+                    .ok_or_else(|| Error::DeclaredHeaderAbsent(h.to_string()))?;
+                Ok((h, hval.as_ref()))
             })
-            .collect::<anyhow::Result<_>>()?;
+            .collect::<Result<_, Error>>()?;
 
         Ok(ordered_headers)
     }
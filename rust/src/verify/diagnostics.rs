@@ -0,0 +1,105 @@
+use std::fmt;
+
+use crate::{base64::ToUrlSafeBase64, crypto, Error, JwsHeader};
+
+use super::{parse_tl_signature, CustomVerifier};
+
+/// How the request body is included in the [`VerifyDiagnostics`] signing payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagnosticBody {
+    /// The body length & url-safe base64 SHA-256 digest, e.g.
+    /// `<2 byte body sha256 RBNvo1WzZ4oRRq0W9-hknpT7T8If536DEMBg9hyq_4o>`.
+    #[default]
+    Digest,
+    /// The raw body, lossily converted to utf8.
+    Raw,
+}
+
+/// Details of a failed `Tl-Signature` verification, see
+/// [`Verifier::verify_diagnostic`](crate::Verifier::verify_diagnostic).
+///
+/// Contains no key material or signature bytes & may be logged. Note the signing
+/// payload includes the signed header values.
+///
+/// Display prints the failed check, jws header & signing payload, e.g.
+/// ```txt
+/// signature verification failed
+/// jws header: {"alg":"ES512","kid":"45fc75cf-5649-4134-84b3-192c2c78e990","tl_version":"2","tl_headers":"Idempotency-Key"}
+/// signing payload:
+/// POST /payouts
+/// Idempotency-Key: idemp-123
+/// <2 byte body sha256 RBNvo1WzZ4oRRq0W9-hknpT7T8If536DEMBg9hyq_4o>
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct VerifyDiagnostics {
+    /// The failed check.
+    pub error: Error,
+    /// The decoded jws header, `None` if the `Tl-Signature` is malformed.
+    pub jws_header: Option<JwsHeader<'static>>,
+    /// The v2 signing payload reconstructed from the request, with the body
+    /// represented as configured by [`DiagnosticBody`].
+    ///
+    /// `None` if the payload cannot be built, e.g. a declared header is missing
+    /// from the request.
+    pub signing_payload: Option<String>,
+}
+
+impl VerifyDiagnostics {
+    pub(crate) fn new(
+        error: Error,
+        verifier: &CustomVerifier<'_>,
+        tl_signature: &str,
+        body: DiagnosticBody,
+    ) -> Self {
+        let jws_header = parse_tl_signature(tl_signature)
+            .ok()
+            .map(|parsed| parsed.header.into_owned());
+        let signing_payload = jws_header.as_ref().and_then(|jws_header| {
+            let payload = match body {
+                DiagnosticBody::Digest => {
+                    let mut payload = verifier.signing_payload(jws_header, &[]).ok()?;
+                    let digest = crypto::sha256(&verifier.body).to_url_safe_base64();
+                    let body_len = verifier.body.len();
+                    payload.extend(format!("<{body_len} byte body sha256 {digest}>").bytes());
+                    payload
+                }
+                DiagnosticBody::Raw => verifier.signing_payload(jws_header, &verifier.body).ok()?,
+            };
+            Some(String::from_utf8_lossy(&payload).into_owned())
+        });
+
+        Self {
+            error,
+            jws_header,
+            signing_payload,
+        }
+    }
+}
+
+impl fmt::Display for VerifyDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(jws_header) = &self.jws_header {
+            let jws_header = serde_json::to_string(jws_header).map_err(|_| fmt::Error)?;
+            write!(f, "\njws header: {jws_header}")?;
+        }
+        if let Some(signing_payload) = &self.signing_payload {
+            write!(f, "\nsigning payload:\n{signing_payload}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VerifyDiagnostics {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        // display already includes the error
+        self.error.source()
+    }
+}
+
+impl From<Box<VerifyDiagnostics>> for Error {
+    fn from(diagnostics: Box<VerifyDiagnostics>) -> Self {
+        diagnostics.error
+    }
+}
@@ -1,18 +1,31 @@
-use std::fmt;
+use std::{borrow::Cow, fmt, sync::Arc, time::Duration};
 
-use anyhow::anyhow;
 use indexmap::{IndexMap, IndexSet};
 
 use crate::{
-    base64::DecodeUrlSafeBase64, http::HeaderName, jws::TlVersion, openssl, Error, JwsHeader,
-    Method, Unset,
+    base64::DecodeUrlSafeBase64, crypto, http::HeaderName, jws::TlVersion, owned::IntoOwned,
+    sign::owned_headers, Error, JwsHeader, Method, Unset,
 };
 
-pub use self::custom_verifer::CustomVerifier;
+pub use self::custom_verifer::{CustomVerifier, TrailingSlash};
+pub use self::diagnostics::{DiagnosticBody, VerifyDiagnostics};
+pub(crate) use self::replay::ReplayCheck;
+pub use self::replay::{AsyncReplayGuard, InMemoryReplayGuard, ReplayGuard, ReplayKey};
+pub use self::request_verifier::{RequestVerifier, VersionPolicy};
+pub use self::verified_signature::VerifiedSignature;
 use self::verifier_v1::VerifierV1;
+pub use self::verifying_key::{VerifyingKey, VerifyingKeySet};
+pub use self::webhook_verifier::WebhookVerifier;
+use self::webhook_verifier::WEBHOOK_TIMESTAMP_HEADER;
 
 mod custom_verifer;
+mod diagnostics;
+mod replay;
+mod request_verifier;
+mod verified_signature;
 mod verifier_v1;
+mod verifying_key;
+mod webhook_verifier;
 
 /// Builder to verify a request against a `Tl-Signature` header.
 ///
@@ -32,23 +45,53 @@ mod verifier_v1;
 ///     .expect("verify");
 /// # }
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct VerifierBuilder<'a, Pk, Body, Method, Path> {
     public_key: Pk,
     body: Body,
     method: Method,
     path: Path,
-    headers: IndexMap<HeaderName<'a>, &'a [u8]>,
+    headers: IndexMap<HeaderName<'a>, Cow<'a, [u8]>>,
     required_headers: IndexSet<HeaderName<'a>>,
+    replay_guard: Option<ReplayCheck<'a>>,
+    trailing_slash: TrailingSlash,
 }
 
 /// Public key for verification.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum PublicKey<'a> {
     /// Public key PEM.
-    Pem(&'a [u8]),
+    Pem(Cow<'a, [u8]>),
     /// JWKs JSON response.
-    Jwks(&'a [u8]),
+    Jwks(Cow<'a, [u8]>),
+    /// Pre-parsed keys indexed by `kid`.
+    KeySet(Cow<'a, VerifyingKeySet>),
+}
+
+impl PublicKey<'_> {
+    /// Parse, or lookup, the public key to verify a signature with the given `kid`.
+    pub(crate) fn resolve(&self, kid: &str) -> Result<Cow<'_, crypto::EcPublicKey>, Error> {
+        match self {
+            PublicKey::Pem(pem) => crypto::parse_ec_public_key(pem).map(Cow::Owned),
+            PublicKey::Jwks(jwks) => crypto::find_and_parse_ec_jwk(kid, jwks).map(Cow::Owned),
+            PublicKey::KeySet(keys) => keys
+                .get(kid)
+                .map(|k| Cow::Borrowed(&k.key))
+                .ok_or_else(|| Error::UnknownKid(kid.to_owned())),
+        }
+    }
+}
+
+impl IntoOwned for PublicKey<'_> {
+    type Owned = PublicKey<'static>;
+
+    fn into_owned(self) -> PublicKey<'static> {
+        match self {
+            PublicKey::Pem(pem) => PublicKey::Pem(IntoOwned::into_owned(pem)),
+            PublicKey::Jwks(jwks) => PublicKey::Jwks(IntoOwned::into_owned(jwks)),
+            PublicKey::KeySet(keys) => PublicKey::KeySet(IntoOwned::into_owned(keys)),
+        }
+    }
 }
 
 /// Debug does not display key info.
@@ -62,38 +105,60 @@ impl<'a> VerifierBuilder<'a, PublicKey<'a>, Unset, Unset, Unset> {
     /// Add public key via pem.
     pub fn pem(pem: &'a [u8]) -> VerifierBuilder<'a, PublicKey<'a>, Unset, Unset, Unset> {
         VerifierBuilder {
-            public_key: PublicKey::Pem(pem),
+            public_key: PublicKey::Pem(Cow::Borrowed(pem)),
             body: Unset,
             method: Unset,
             path: Unset,
             headers: <_>::default(),
             required_headers: <_>::default(),
+            replay_guard: None,
+            trailing_slash: TrailingSlash::default(),
         }
     }
 
     /// Add public key via a jwks.
     pub fn jwks(jwk: &'a [u8]) -> VerifierBuilder<'a, PublicKey<'a>, Unset, Unset, Unset> {
         VerifierBuilder {
-            public_key: PublicKey::Jwks(jwk),
+            public_key: PublicKey::Jwks(Cow::Borrowed(jwk)),
+            body: Unset,
+            method: Unset,
+            path: Unset,
+            headers: <_>::default(),
+            required_headers: <_>::default(),
+            replay_guard: None,
+            trailing_slash: TrailingSlash::default(),
+        }
+    }
+
+    /// Add pre-parsed public keys, the key matching the signature `kid` will be used.
+    pub fn key_set(
+        keys: &'a VerifyingKeySet,
+    ) -> VerifierBuilder<'a, PublicKey<'a>, Unset, Unset, Unset> {
+        VerifierBuilder {
+            public_key: PublicKey::KeySet(Cow::Borrowed(keys)),
             body: Unset,
             method: Unset,
             path: Unset,
             headers: <_>::default(),
             required_headers: <_>::default(),
+            replay_guard: None,
+            trailing_slash: TrailingSlash::default(),
         }
     }
 }
 
 impl<'a, Pk, Method, Path> VerifierBuilder<'a, Pk, Unset, Method, Path> {
     /// Add the full received request body.
-    pub fn body(self, body: &'a [u8]) -> VerifierBuilder<'a, Pk, &'a [u8], Method, Path> {
+    pub fn body(self, body: &'a [u8]) -> VerifierBuilder<'a, Pk, Cow<'a, [u8]>, Method, Path> {
         VerifierBuilder {
             public_key: self.public_key,
-            body,
+            body: Cow::Borrowed(body),
             method: self.method,
             path: self.path,
             headers: self.headers,
             required_headers: self.required_headers,
+            replay_guard: self.replay_guard,
+            trailing_slash: self.trailing_slash,
         }
     }
 }
@@ -108,6 +173,8 @@ impl<'a, Pk, Body, Path> VerifierBuilder<'a, Pk, Body, Unset, Path> {
             path: self.path,
             headers: self.headers,
             required_headers: self.required_headers,
+            replay_guard: self.replay_guard,
+            trailing_slash: self.trailing_slash,
         }
     }
 }
@@ -117,7 +184,7 @@ impl<'a, Pk, Body, Method> VerifierBuilder<'a, Pk, Body, Method, Unset> {
     ///
     /// # Panics
     /// If `path` does not start with a '/' char.
-    pub fn path(self, path: &'a str) -> VerifierBuilder<'a, Pk, Body, Method, &'a str> {
+    pub fn path(self, path: &'a str) -> VerifierBuilder<'a, Pk, Body, Method, Cow<'a, str>> {
         assert!(
             path.starts_with('/'),
             "Invalid path \"{path}\" must start with '/'"
@@ -126,9 +193,11 @@ impl<'a, Pk, Body, Method> VerifierBuilder<'a, Pk, Body, Method, Unset> {
             public_key: self.public_key,
             body: self.body,
             method: self.method,
-            path,
+            path: Cow::Borrowed(path),
             headers: self.headers,
             required_headers: self.required_headers,
+            replay_guard: self.replay_guard,
+            trailing_slash: self.trailing_slash,
         }
     }
 }
@@ -141,7 +210,8 @@ impl<'a, Pk, Body, Method, Path> VerifierBuilder<'a, Pk, Body, Method, Path> {
     /// in the jws signature header will be ignored unless required using
     /// [`Verifier::require_header`].
     pub fn header(mut self, key: &'a str, value: &'a [u8]) -> Self {
-        self.headers.insert(HeaderName(key), value);
+        self.headers
+            .insert(HeaderName::new(key), Cow::Borrowed(value));
         self
     }
 
@@ -158,8 +228,11 @@ impl<'a, Pk, Body, Method, Path> VerifierBuilder<'a, Pk, Body, Method, Path> {
     ///     .headers([("X-Head-A", "123".as_bytes()), ("X-Head-B", "345".as_bytes())]);
     /// ```
     pub fn headers(mut self, headers: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Self {
-        self.headers
-            .extend(headers.into_iter().map(|(k, v)| (HeaderName(k), v)));
+        self.headers.extend(
+            headers
+                .into_iter()
+                .map(|(k, v)| (HeaderName::new(k), Cow::Borrowed(v))),
+        );
         self
     }
 
@@ -168,12 +241,95 @@ impl<'a, Pk, Body, Method, Path> VerifierBuilder<'a, Pk, Body, Method, Path> {
     ///
     /// Signatures missing these will fail verification.
     pub fn require_header(mut self, key: &'a str) -> Self {
-        self.required_headers.insert(HeaderName(key));
+        self.required_headers.insert(HeaderName::new(key));
+        self
+    }
+
+    /// Reject signatures that have already been successfully verified, as
+    /// identified by `key`, using the given [`ReplayGuard`]. Verified keys are
+    /// remembered for `ttl`, which should be at least as long as signatures are
+    /// otherwise accepted, e.g. twice any [`WebhookVerifier::tolerance`].
+    ///
+    /// [`ReplayKey::Header`] names are required to be included in the `Tl-Signature`.
+    /// See [`VerifierBuilder::require_header`].
+    pub fn replay_guard(
+        self,
+        guard: Arc<dyn ReplayGuard>,
+        key: ReplayKey<'a>,
+        ttl: Duration,
+    ) -> Self {
+        self.replay_check(ReplayCheck::new(guard, key, ttl))
+    }
+
+    pub(crate) fn replay_check(mut self, replay_guard: ReplayCheck<'a>) -> Self {
+        if let Some(header) = replay_guard.header() {
+            self.required_headers.insert(header.clone());
+        }
+        self.replay_guard = Some(replay_guard);
         self
     }
+
+    /// Sets how signatures of the request path with, or without, a trailing slash
+    /// are treated, default [`TrailingSlash::Tolerant`].
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+}
+
+impl<Pk, Body, Method, Path> VerifierBuilder<'_, Pk, Body, Method, Path>
+where
+    Pk: IntoOwned,
+    Body: IntoOwned,
+    Method: IntoOwned,
+    Path: IntoOwned,
+{
+    /// Converts into a `'static` builder owning all its data, copying any borrowed
+    /// values.
+    ///
+    /// This allows a partially configured builder to be stored, e.g. in middleware,
+    /// cloned for each request & sent across threads.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let jwks: Vec<u8> = unimplemented!();
+    /// let verifier = truelayer_signing::verify_with_jwks(&jwks)
+    ///     .method(truelayer_signing::Method::Post)
+    ///     .path("/webhook")
+    ///     .require_header("X-Tl-Webhook-Timestamp")
+    ///     .into_owned();
+    ///
+    /// std::thread::spawn(move || {
+    ///     # let (headers, body, tl_signature): (Vec<(String, Vec<u8>)>, Vec<u8>, String) = unimplemented!();
+    ///     let result = verifier
+    ///         .headers(headers.iter().map(|(k, v)| (k.as_str(), v.as_slice())))
+    ///         .body(&body)
+    ///         .build_verifier()
+    ///         .verify(&tl_signature);
+    /// });
+    /// ```
+    pub fn into_owned(
+        self,
+    ) -> VerifierBuilder<'static, Pk::Owned, Body::Owned, Method::Owned, Path::Owned> {
+        VerifierBuilder {
+            public_key: self.public_key.into_owned(),
+            body: self.body.into_owned(),
+            method: self.method.into_owned(),
+            path: self.path.into_owned(),
+            headers: owned_headers(self.headers),
+            required_headers: owned_header_names(self.required_headers),
+            replay_guard: self.replay_guard.map(ReplayCheck::into_owned),
+            trailing_slash: self.trailing_slash,
+        }
+    }
+}
+
+/// Copies any borrowed header names.
+fn owned_header_names(names: IndexSet<HeaderName<'_>>) -> IndexSet<HeaderName<'static>> {
+    names.into_iter().map(HeaderName::into_owned).collect()
 }
 
-impl<'a> VerifierBuilder<'a, PublicKey<'a>, &'a [u8], Method, &'a str> {
+impl<'a> VerifierBuilder<'a, PublicKey<'a>, Cow<'a, [u8]>, Method, Cow<'a, str>> {
     /// Build a V2 Verifier see [`Verifier`].
     ///
     /// requires the public key, body, method, and path to be set to call this function.
@@ -185,13 +341,28 @@ impl<'a> VerifierBuilder<'a, PublicKey<'a>, &'a [u8], Method, &'a str> {
                 path: self.path,
                 headers: self.headers,
                 required_headers: self.required_headers,
+                replay_guard: self.replay_guard,
+                trailing_slash: self.trailing_slash,
             },
             public_key: self.public_key,
         }
     }
+
+    /// Build a webhook Verifier see [`WebhookVerifier`].
+    ///
+    /// `X-Tl-Webhook-Timestamp` is required to be included in the signature and must be
+    /// within a tolerance of the current time.
+    ///
+    /// requires the public key, body, method, and path to be set to call this function.
+    pub fn build_webhook_verifier(self) -> WebhookVerifier<'a> {
+        WebhookVerifier::new(
+            self.require_header(WEBHOOK_TIMESTAMP_HEADER)
+                .build_verifier(),
+        )
+    }
 }
 
-impl<'a> VerifierBuilder<'a, PublicKey<'a>, &'a [u8], Unset, Unset> {
+impl<'a> VerifierBuilder<'a, PublicKey<'a>, Cow<'a, [u8]>, Unset, Unset> {
     /// Build a V1 Verifier see [`VerifierV1`].
     ///
     /// requires the public key and body to be set to call this function.
@@ -199,6 +370,7 @@ impl<'a> VerifierBuilder<'a, PublicKey<'a>, &'a [u8], Unset, Unset> {
         VerifierV1 {
             public_key: self.public_key,
             body: self.body,
+            replay_guard: self.replay_guard,
         }
     }
 }
@@ -221,6 +393,7 @@ impl<'a> VerifierBuilder<'a, PublicKey<'a>, &'a [u8], Unset, Unset> {
 ///     .expect("verify");
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct Verifier<'a> {
     base: CustomVerifier<'a>,
     public_key: PublicKey<'a>,
@@ -239,23 +412,82 @@ impl<'a> Verifier<'a> {
     /// Supports v2 full request signatures.
     ///
     /// Returns `Err(_)` if verification fails.
-    pub fn verify(self, tl_signature: &'a str) -> Result<(), Error> {
+    pub fn verify(self, tl_signature: &'a str) -> Result<VerifiedSignature, Error> {
         let parsed_tl_signature = parse_tl_signature(tl_signature)?;
         self.verify_parsed(parsed_tl_signature)
     }
 
-    fn verify_parsed(self, parsed_tl_signature: ParsedTlSignature<'a>) -> Result<(), Error> {
-        let public_key = match self.public_key {
-            PublicKey::Pem(pem) => openssl::parse_ec_public_key(pem),
-            PublicKey::Jwks(jwks) => {
-                openssl::find_and_parse_ec_jwk(&parsed_tl_signature.header.kid, jwks)
-            }
+    /// Converts into a `'static` verifier owning all its data, copying any borrowed
+    /// values.
+    pub fn into_owned(self) -> Verifier<'static> {
+        Verifier {
+            base: self.base.into_owned(),
+            public_key: self.public_key.into_owned(),
         }
-        .map_err(Error::InvalidKey)?;
+    }
+
+    /// Verify the given `Tl-Signature` header value, as [`Verifier::verify`], returning
+    /// [`VerifyDiagnostics`] on failure with the reconstructed signing payload to help
+    /// debug signature mismatches.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), truelayer_signing::Error> {
+    /// # let (public_key, idempotency_key, body, tl_signature) = unimplemented!();
+    /// let verified = truelayer_signing::verify_with_pem(public_key)
+    ///     .method(truelayer_signing::Method::Post)
+    ///     .path("/payouts")
+    ///     .header("Idempotency-Key", idempotency_key)
+    ///     .body(body)
+    ///     .build_verifier()
+    ///     .verify_diagnostic(tl_signature, truelayer_signing::DiagnosticBody::Digest)
+    ///     .inspect_err(|diagnostics| eprintln!("verify failed: {diagnostics}"))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn verify_diagnostic(
+        self,
+        tl_signature: &'a str,
+        body: DiagnosticBody,
+    ) -> Result<VerifiedSignature, Box<VerifyDiagnostics>> {
+        parse_tl_signature(tl_signature)
+            .and_then(|parsed_tl_signature| self.verify_parsed(parsed_tl_signature))
+            .map_err(|error| {
+                Box::new(VerifyDiagnostics::new(
+                    error,
+                    &self.base,
+                    tl_signature,
+                    body,
+                ))
+            })
+    }
+
+    fn verify_parsed(
+        &self,
+        parsed_tl_signature: ParsedTlSignature<'a>,
+    ) -> Result<VerifiedSignature, Error> {
+        let (verified, replay_key) = self.verify_parsed_unrecorded(parsed_tl_signature)?;
+        self.base.record_replay(replay_key)?;
+        Ok(verified)
+    }
+
+    /// Verify without recording the request with the replay guard, see
+    /// [`CustomVerifier::record_replay`].
+    pub(crate) fn verify_unrecorded(
+        &self,
+        tl_signature: &'a str,
+    ) -> Result<(VerifiedSignature, Option<Vec<u8>>), Error> {
+        self.verify_parsed_unrecorded(parse_tl_signature(tl_signature)?)
+    }
+
+    fn verify_parsed_unrecorded(
+        &self,
+        parsed_tl_signature: ParsedTlSignature<'a>,
+    ) -> Result<(VerifiedSignature, Option<Vec<u8>>), Error> {
+        let public_key = self.public_key.resolve(&parsed_tl_signature.header.kid)?;
 
         self.base
-            .verify_parsed_with(parsed_tl_signature, |payload, signature| {
-                openssl::verify_es512(&public_key, payload, signature).map_err(Error::JwsError)
+            .verify_parsed_unrecorded(parsed_tl_signature, |payload, signature| {
+                crypto::verify_es512(&public_key, payload, signature)
             })
     }
 
@@ -264,18 +496,39 @@ impl<'a> Verifier<'a> {
     /// Supports v1 (body only) & v2 full request signatures.
     ///
     /// Returns `Err(_)` if verification fails.
-    pub fn verify_v1_or_v2(self, tl_signature: &'a str) -> Result<(), Error> {
+    pub fn verify_v1_or_v2(self, tl_signature: &'a str) -> Result<VerifiedSignature, Error> {
+        let (verified, replay_key) = self.verify_v1_or_v2_unrecorded(tl_signature)?;
+        self.base.record_replay(replay_key)?;
+        Ok(verified)
+    }
+
+    /// Verify v1 or v2 signatures without recording the request with the replay guard,
+    /// see [`Verifier::verify_v1_or_v2`].
+    pub(crate) fn verify_v1_or_v2_unrecorded(
+        &self,
+        tl_signature: &'a str,
+    ) -> Result<(VerifiedSignature, Option<Vec<u8>>), Error> {
         let parsed_tl_signature = parse_tl_signature(tl_signature)?;
 
         match &parsed_tl_signature.header.tl_version {
-            None | Some(TlVersion::V1) => VerifierV1 {
-                public_key: self.public_key,
-                body: self.base.body,
-            }
-            .verify_parsed_body_only(parsed_tl_signature),
-            Some(TlVersion::V2) => self.verify_parsed(parsed_tl_signature),
+            None | Some(TlVersion::V1) => verifier_v1::verify_body_only_unrecorded(
+                &self.public_key,
+                &self.base.body,
+                self.base.replay_guard.as_ref(),
+                parsed_tl_signature,
+            ),
+            Some(TlVersion::V2) => self.verify_parsed_unrecorded(parsed_tl_signature),
         }
     }
+
+    /// Record a request verified by [`Verifier::verify_unrecorded`] with the, possibly
+    /// async, replay guard, failing if it is a replay.
+    pub(crate) async fn record_replay_async(
+        &self,
+        replay_key: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.base.record_replay_async(replay_key).await
+    }
 }
 
 /// Parsed `Tl-Signature` header value.
@@ -286,21 +539,30 @@ pub(crate) struct ParsedTlSignature<'a> {
 }
 
 /// Parse a tl signature header value into `(header, header_base64, signature)`.
-pub(crate) fn parse_tl_signature(tl_signature: &str) -> Result<ParsedTlSignature, Error> {
+pub(crate) fn parse_tl_signature(tl_signature: &str) -> Result<ParsedTlSignature<'_>, Error> {
     let (header_b64, signature_b64) = tl_signature
         .split_once("..")
-        .ok_or_else(|| Error::JwsError(anyhow!("invalid signature format")))?;
+        .ok_or_else(|| Error::malformed_signature("invalid signature format"))?;
 
-    let header: JwsHeader = serde_json::from_slice(
-        &header_b64
-            .decode_url_safe_base64()
-            .map_err(|e| Error::JwsError(anyhow!("header decode failed: {}", e)))?,
-    )
-    .map_err(|e| Error::JwsError(anyhow!("header decode failed: {}", e)))?;
+    let header: JwsHeader =
+        serde_json::from_slice(&header_b64.decode_url_safe_base64().map_err(|e| {
+            Error::MalformedSignature {
+                reason: "header decode failed",
+                source: Some(e.into()),
+            }
+        })?)
+        .map_err(|e| Error::MalformedSignature {
+            reason: "header decode failed",
+            source: Some(e.into()),
+        })?;
 
-    let signature = signature_b64
-        .decode_url_safe_base64()
-        .map_err(|e| Error::JwsError(anyhow!("signature decode failed: {}", e)))?;
+    let signature =
+        signature_b64
+            .decode_url_safe_base64()
+            .map_err(|e| Error::MalformedSignature {
+                reason: "signature decode failed",
+                source: Some(e.into()),
+            })?;
 
     Ok(ParsedTlSignature {
         header,
@@ -0,0 +1,290 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use indexmap::IndexMap;
+
+use crate::{crypto, http::HeaderName, BoxError, Error};
+
+/// Records successfully verified requests so repeat deliveries can be rejected.
+///
+/// Consulted after a signature is successfully verified, see [`VerifierBuilder::replay_guard`].
+///
+/// [`VerifierBuilder::replay_guard`]: crate::VerifierBuilder::replay_guard
+pub trait ReplayGuard: Send + Sync {
+    /// Record `key` as seen for at least `ttl`.
+    ///
+    /// Returns `Ok(false)` if `key` has already been seen, i.e. this is a replay.
+    /// Errors, e.g. a failure to reach a shared store, fail verification with
+    /// [`Error::ReplayGuardFailed`].
+    fn insert(&self, key: &[u8], ttl: Duration) -> Result<bool, BoxError>;
+}
+
+/// Async [`ReplayGuard`], e.g. backed by a shared store such as Redis, used by
+/// async verifiers, see [`VerifyConfig::replay_guard`](crate::VerifyConfig::replay_guard).
+pub trait AsyncReplayGuard: Send + Sync {
+    /// Record `key` as seen for at least `ttl`.
+    ///
+    /// Returns `Ok(false)` if `key` has already been seen, i.e. this is a replay.
+    /// Errors, e.g. a failure to reach a shared store, fail verification with
+    /// [`Error::ReplayGuardFailed`].
+    fn insert(
+        &self,
+        key: &[u8],
+        ttl: Duration,
+    ) -> impl Future<Output = Result<bool, BoxError>> + Send;
+}
+
+/// Object safe [`AsyncReplayGuard`].
+pub(crate) trait DynAsyncReplayGuard: Send + Sync {
+    fn insert<'a>(
+        &'a self,
+        key: &'a [u8],
+        ttl: Duration,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, BoxError>> + Send + 'a>>;
+}
+
+impl<G: AsyncReplayGuard> DynAsyncReplayGuard for G {
+    fn insert<'a>(
+        &'a self,
+        key: &'a [u8],
+        ttl: Duration,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, BoxError>> + Send + 'a>> {
+        Box::pin(AsyncReplayGuard::insert(self, key, ttl))
+    }
+}
+
+/// What identifies a request for replay protection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayKey<'a> {
+    /// The signed content, a SHA-256 digest of the jws header & signing payload.
+    ///
+    /// Unlike the signature bytes, which may be altered & still verify, this
+    /// cannot change without invalidating the signature.
+    Signature,
+    /// The value of a signed header, e.g. `Idempotency-Key`.
+    ///
+    /// The header is required to be included in the signature.
+    Header(&'a str),
+}
+
+/// A [`ReplayGuard`], or [`AsyncReplayGuard`], & the [`ReplayKey`] identifying requests.
+#[derive(Clone)]
+pub(crate) struct ReplayCheck<'a> {
+    guard: Guard,
+    /// Signed header identifying requests, otherwise the signed content.
+    header: Option<HeaderName<'a>>,
+    ttl: Duration,
+}
+
+#[derive(Clone)]
+enum Guard {
+    Blocking(Arc<dyn ReplayGuard>),
+    Async(Arc<dyn DynAsyncReplayGuard>),
+}
+
+impl fmt::Debug for ReplayCheck<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ReplayCheck")
+            .field("header", &self.header)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> ReplayCheck<'a> {
+    pub(crate) fn new(guard: Arc<dyn ReplayGuard>, key: ReplayKey<'a>, ttl: Duration) -> Self {
+        Self::with_guard(Guard::Blocking(guard), key, ttl)
+    }
+
+    pub(crate) fn new_async(
+        guard: Arc<dyn DynAsyncReplayGuard>,
+        key: ReplayKey<'a>,
+        ttl: Duration,
+    ) -> Self {
+        Self::with_guard(Guard::Async(guard), key, ttl)
+    }
+
+    fn with_guard(guard: Guard, key: ReplayKey<'a>, ttl: Duration) -> Self {
+        let header = match key {
+            ReplayKey::Signature => None,
+            ReplayKey::Header(name) => Some(HeaderName::new(name)),
+        };
+        Self { guard, header, ttl }
+    }
+
+    /// The header required to be included in the signature, if any.
+    pub(crate) fn header(&self) -> Option<&HeaderName<'a>> {
+        self.header.as_ref()
+    }
+
+    /// The key identifying a verified request, from the verified jws signing input
+    /// `{header_b64}.{payload_b64}` or the signed header.
+    pub(crate) fn key(
+        &self,
+        signing_input: &[u8],
+        signed_headers: &IndexMap<HeaderName<'_>, &[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        match &self.header {
+            None => Ok(crypto::sha256(signing_input).to_vec()),
+            Some(name) => signed_headers
+                .get(name)
+                .map(|value| value.to_vec())
+                .ok_or_else(|| Error::MissingRequiredHeader(name.to_string())),
+        }
+    }
+
+    /// Record the key of a verified request, failing if it is a replay.
+    pub(crate) fn record(&self, key: &[u8]) -> Result<(), Error> {
+        match &self.guard {
+            Guard::Blocking(guard) => recorded(guard.insert(key, self.ttl)),
+            Guard::Async(_) => Err(Error::ReplayGuardFailed(
+                "async replay guard cannot be used by a blocking verifier".into(),
+            )),
+        }
+    }
+
+    /// Record the key of a verified request, failing if it is a replay.
+    pub(crate) async fn record_async(&self, key: &[u8]) -> Result<(), Error> {
+        match &self.guard {
+            Guard::Blocking(guard) => recorded(guard.insert(key, self.ttl)),
+            Guard::Async(guard) => recorded(guard.insert(key, self.ttl).await),
+        }
+    }
+
+    pub(crate) fn into_owned(self) -> ReplayCheck<'static> {
+        ReplayCheck {
+            guard: self.guard,
+            header: self.header.map(HeaderName::into_owned),
+            ttl: self.ttl,
+        }
+    }
+}
+
+fn recorded(inserted: Result<bool, BoxError>) -> Result<(), Error> {
+    match inserted {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Error::Replayed),
+        Err(err) => Err(Error::ReplayGuardFailed(err)),
+    }
+}
+
+/// In-memory [`ReplayGuard`] remembering each key for the ttl it was inserted with.
+///
+/// Only suitable for a single process, use a shared store if requests may be
+/// delivered to any of multiple instances.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (public_key, idempotency_key, body, tl_signature) = unimplemented!();
+/// use std::{sync::Arc, time::Duration};
+/// use truelayer_signing::{InMemoryReplayGuard, ReplayKey};
+///
+/// // shared by all requests
+/// let replay_guard = Arc::new(InMemoryReplayGuard::new());
+///
+/// truelayer_signing::verify_with_pem(public_key)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/payouts")
+///     .replay_guard(
+///         replay_guard.clone(),
+///         ReplayKey::Header("Idempotency-Key"),
+///         Duration::from_secs(10 * 60),
+///     )
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryReplayGuard {
+    seen: Mutex<Seen>,
+}
+
+#[derive(Debug, Default)]
+struct Seen {
+    /// Key expiry times.
+    keys: HashMap<Vec<u8>, Instant>,
+    /// Keys in expiry order.
+    expiry: BTreeSet<(Instant, Vec<u8>)>,
+}
+
+impl InMemoryReplayGuard {
+    /// Create an empty guard.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of unexpired keys.
+    pub fn len(&self) -> usize {
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        seen.remove_expired(Instant::now());
+        seen.keys.len()
+    }
+
+    /// Returns `true` if there are no unexpired keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Seen {
+    fn remove_expired(&mut self, now: Instant) {
+        while self
+            .expiry
+            .first()
+            .is_some_and(|(expires, _)| *expires <= now)
+        {
+            if let Some((_, key)) = self.expiry.pop_first() {
+                self.keys.remove(&key);
+            }
+        }
+    }
+}
+
+impl ReplayGuard for InMemoryReplayGuard {
+    fn insert(&self, key: &[u8], ttl: Duration) -> Result<bool, BoxError> {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        seen.remove_expired(now);
+        if seen.keys.contains_key(key) {
+            return Ok(false);
+        }
+        let expires = now + ttl;
+        seen.keys.insert(key.to_vec(), expires);
+        seen.expiry.insert((expires, key.to_vec()));
+        Ok(true)
+    }
+}
+
+impl AsyncReplayGuard for InMemoryReplayGuard {
+    async fn insert(&self, key: &[u8], ttl: Duration) -> Result<bool, BoxError> {
+        ReplayGuard::insert(self, key, ttl)
+    }
+}
+
+#[test]
+fn in_memory_replay_guard() {
+    let guard = InMemoryReplayGuard::new();
+    let ttl = Duration::from_secs(60);
+    assert!(ReplayGuard::insert(&guard, b"a", ttl).unwrap());
+    assert!(ReplayGuard::insert(&guard, b"b", ttl).unwrap());
+    assert!(!ReplayGuard::insert(&guard, b"a", ttl).unwrap());
+    assert_eq!(guard.len(), 2);
+}
+
+#[test]
+fn in_memory_replay_guard_ttl() {
+    let guard = InMemoryReplayGuard::new();
+    assert!(ReplayGuard::insert(&guard, b"a", Duration::ZERO).unwrap());
+    assert!(ReplayGuard::insert(&guard, b"a", Duration::ZERO).unwrap());
+    assert!(ReplayGuard::insert(&guard, b"b", Duration::from_secs(60)).unwrap());
+    assert_eq!(guard.len(), 1);
+}
@@ -0,0 +1,105 @@
+use crate::{Error, Method, VerifiedSignature, VerifyConfig};
+
+/// Signature versions accepted by a [`RequestVerifier`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Only v2 full request signatures, see [`Verifier::verify`](crate::Verifier::verify).
+    #[default]
+    V2,
+    /// v1 body only & v2 full request signatures, see
+    /// [`Verifier::verify_v1_or_v2`](crate::Verifier::verify_v1_or_v2).
+    ///
+    /// v1 signatures do not include headers, so are rejected with
+    /// [`Error::MissingRequiredHeader`] if any headers are required.
+    V1OrV2,
+}
+
+/// Verifies many requests using keys & a policy configured once, e.g. at startup,
+/// & shared across handlers.
+///
+/// A [`VerifyConfig`] with a [`VersionPolicy`].
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> Result<(), truelayer_signing::Error> {
+/// # let (jwks, body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// let keys = truelayer_signing::VerifyingKeySet::from_jwks(jwks)?;
+/// let config = truelayer_signing::VerifyConfig::new(keys)
+///     .require_header("Idempotency-Key")
+///     .trailing_slash(truelayer_signing::TrailingSlash::Strict);
+/// let verifier = truelayer_signing::RequestVerifier::new(config);
+///
+/// verifier
+///     .verify(
+///         truelayer_signing::Method::Post,
+///         "/payouts",
+///         headers,
+///         body,
+///         tl_signature,
+///     )
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestVerifier {
+    config: VerifyConfig,
+    version_policy: VersionPolicy,
+}
+
+impl RequestVerifier {
+    /// Verify requests using the given keys & policy.
+    pub fn new(config: VerifyConfig) -> Self {
+        Self {
+            config,
+            version_policy: VersionPolicy::default(),
+        }
+    }
+
+    /// Sets the accepted signature versions, default [`VersionPolicy::V2`].
+    pub fn version_policy(mut self, version_policy: VersionPolicy) -> Self {
+        self.version_policy = version_policy;
+        self
+    }
+
+    /// The keys & policy used to verify requests.
+    pub fn config(&self) -> &VerifyConfig {
+        &self.config
+    }
+
+    /// Verify the given `Tl-Signature` header value against a request.
+    ///
+    /// All request `headers` may be passed, any headers not mentioned in the
+    /// jws signature header will be ignored.
+    ///
+    /// Returns `Err(_)` if verification fails.
+    ///
+    /// # Panics
+    /// If `path` does not start with a '/' char.
+    pub async fn verify<'h>(
+        &self,
+        method: Method,
+        path: &str,
+        headers: impl IntoIterator<Item = (&'h str, &'h [u8])>,
+        body: &[u8],
+        tl_signature: &str,
+    ) -> Result<VerifiedSignature, Error> {
+        let headers = headers.into_iter().collect();
+        self.config
+            .verify_signature(
+                method,
+                path,
+                headers,
+                body,
+                tl_signature,
+                self.version_policy,
+            )
+            .await
+    }
+}
+
+impl From<VerifyConfig> for RequestVerifier {
+    fn from(config: VerifyConfig) -> Self {
+        Self::new(config)
+    }
+}
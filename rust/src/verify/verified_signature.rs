@@ -0,0 +1,63 @@
+use crate::{http::HeaderName, JwsHeader, TlVersion};
+
+/// Details of a successfully verified `Tl-Signature`, e.g. for auditing or
+/// to decide which request headers may be trusted.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (public_key, idempotency_key, body, tl_signature) = unimplemented!();
+/// let verified = truelayer_signing::verify_with_pem(public_key)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/payouts")
+///     .header("Idempotency-Key", idempotency_key)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+///
+/// if verified.signs_header("Idempotency-Key") {
+///     // the Idempotency-Key is authentic
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VerifiedSignature {
+    /// Signing key id.
+    pub kid: String,
+    /// Signing scheme version.
+    pub version: TlVersion,
+    /// Names of the headers included in the signature, in signed order.
+    ///
+    /// Empty for v1 signatures, which only include the body.
+    pub signed_headers: Vec<String>,
+    /// The signed path, if it differs from the request path by a trailing slash,
+    /// e.g. `/payouts/` for a `/payouts` request.
+    ///
+    /// Set when the [`TrailingSlash::Tolerant`](crate::TrailingSlash::Tolerant)
+    /// fallback matched.
+    pub signed_path: Option<String>,
+    /// JSON Web Key URL, set on webhook signatures.
+    pub jku: Option<String>,
+}
+
+impl VerifiedSignature {
+    pub(crate) fn new(jws_header: JwsHeader<'_>, version: TlVersion) -> Self {
+        Self {
+            kid: jws_header.kid.into_owned(),
+            version,
+            signed_headers: vec![],
+            signed_path: None,
+            jku: jws_header.jku.map(|jku| jku.into_owned()),
+        }
+    }
+
+    /// Returns `true` if the header is included in the signature, matched
+    /// case-insensitively.
+    pub fn signs_header(&self, name: &str) -> bool {
+        let name = HeaderName::new(name);
+        self.signed_headers
+            .iter()
+            .any(|header| HeaderName::new(header) == name)
+    }
+}
@@ -1,13 +1,16 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use crate::{base64::ToUrlSafeBase64, openssl, Error};
+use crate::{base64::ToUrlSafeBase64, crypto, Error};
 
-use super::{parse_tl_signature, ParsedTlSignature, PublicKey};
+use crate::jws::TlVersion;
+
+use super::{parse_tl_signature, ParsedTlSignature, PublicKey, ReplayCheck, VerifiedSignature};
 
 /// A verifier for a request against a `Tl-Signature` header V1.
 pub struct VerifierV1<'a> {
     pub(crate) public_key: PublicKey<'a>,
-    pub(crate) body: &'a [u8],
+    pub(crate) body: Cow<'a, [u8]>,
+    pub(crate) replay_guard: Option<ReplayCheck<'a>>,
 }
 
 /// Debug does not display key info.
@@ -23,7 +26,7 @@ impl<'a> VerifierV1<'a> {
     /// Supports v1 (body only) request signatures.
     ///
     /// Returns `Err(_)` if verification fails.
-    pub fn verify_body_only(self, tl_signature: &'a str) -> Result<(), Error> {
+    pub fn verify_body_only(self, tl_signature: &'a str) -> Result<VerifiedSignature, Error> {
         let parsed_tl_signature = parse_tl_signature(tl_signature)?;
         self.verify_parsed_body_only(parsed_tl_signature)
     }
@@ -31,21 +34,46 @@ impl<'a> VerifierV1<'a> {
     pub(crate) fn verify_parsed_body_only(
         self,
         tl_signature: ParsedTlSignature<'a>,
-    ) -> Result<(), Error> {
-        let ParsedTlSignature {
-            header: jws_header,
-            header_b64,
-            signature,
-        } = tl_signature;
-
-        let public_key = match self.public_key {
-            PublicKey::Pem(pem) => openssl::parse_ec_public_key(pem),
-            PublicKey::Jwks(jwks) => openssl::find_and_parse_ec_jwk(&jws_header.kid, jwks),
+    ) -> Result<VerifiedSignature, Error> {
+        let (verified, replay_key) = verify_body_only_unrecorded(
+            &self.public_key,
+            &self.body,
+            self.replay_guard.as_ref(),
+            tl_signature,
+        )?;
+        if let (Some(replay_guard), Some(key)) = (&self.replay_guard, replay_key) {
+            replay_guard.record(&key)?;
         }
-        .map_err(Error::InvalidKey)?;
-
-        // v1 signature: body only
-        let payload = format!("{header_b64}.{}", self.body.to_url_safe_base64());
-        openssl::verify_es512(&public_key, payload.as_bytes(), &signature).map_err(Error::JwsError)
+        Ok(verified)
     }
 }
+
+/// Verify a v1 (body only) signature without recording the request with the replay
+/// guard, returning the replay key if a replay guard is set.
+pub(crate) fn verify_body_only_unrecorded(
+    public_key: &PublicKey<'_>,
+    body: &[u8],
+    replay_guard: Option<&ReplayCheck<'_>>,
+    tl_signature: ParsedTlSignature<'_>,
+) -> Result<(VerifiedSignature, Option<Vec<u8>>), Error> {
+    let ParsedTlSignature {
+        header: jws_header,
+        header_b64,
+        signature,
+    } = tl_signature;
+
+    let public_key = public_key.resolve(&jws_header.kid)?;
+
+    // v1 signature: body only
+    let payload = format!("{header_b64}.{}", body.to_url_safe_base64());
+    crypto::verify_es512(&public_key, payload.as_bytes(), &signature)?;
+
+    // v1 signatures do not include headers
+    let replay_key = replay_guard
+        .map(|replay_guard| replay_guard.key(payload.as_bytes(), &<_>::default()))
+        .transpose()?;
+    Ok((
+        VerifiedSignature::new(jws_header, TlVersion::V1),
+        replay_key,
+    ))
+}
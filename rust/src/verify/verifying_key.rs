@@ -0,0 +1,192 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::{crypto, Error};
+
+/// A parsed P-521 public key & its `kid`.
+#[derive(Clone)]
+pub struct VerifyingKey {
+    kid: String,
+    pub(crate) key: crypto::EcPublicKey,
+}
+
+/// Debug does not display key info.
+impl fmt::Debug for VerifyingKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("VerifyingKey")
+            .field("kid", &self.kid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VerifyingKey {
+    /// Parse a P-521 public key pem & associate it with the given `kid`.
+    pub fn from_pem(kid: impl Into<String>, public_key_pem: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_public_key(public_key_pem)?,
+        })
+    }
+
+    /// Parse a DER SPKI P-521 public key & associate it with the given `kid`.
+    pub fn from_spki_der(kid: impl Into<String>, public_key_der: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            kid: kid.into(),
+            key: crypto::parse_ec_public_key_spki_der(public_key_der)?,
+        })
+    }
+
+    pub(crate) fn new(kid: String, key: crypto::EcPublicKey) -> Self {
+        Self { kid, key }
+    }
+
+    /// The signing key id.
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// Export the public key as an SPKI `PUBLIC KEY` pem.
+    pub fn to_pem(&self) -> Result<String, Error> {
+        crypto::public_key_to_pem(&self.key).map_err(Error::InvalidKey)
+    }
+
+    /// Export the public key as a JWK, in the form served by a jwks endpoint.
+    ///
+    /// ```json
+    /// {"kty":"EC","alg":"ES512","kid":"...","crv":"P-521","x":"...","y":"..."}
+    /// ```
+    pub fn to_jwk(&self) -> Result<serde_json::Value, Error> {
+        crypto::ec_public_jwk(&self.kid, &self.key).map_err(Error::InvalidKey)
+    }
+}
+
+/// A set of parsed P-521 public keys indexed by `kid`.
+///
+/// Keys are parsed once up front, so a `VerifyingKeySet` can be shared
+/// & reused to verify many signatures without any further parsing.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (jwks, body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// // jwks json of form: {"keys":[...]}
+/// let keys = truelayer_signing::VerifyingKeySet::from_jwks(jwks)?;
+///
+/// truelayer_signing::verify_with_key_set(&keys)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/webhook")
+///     .headers(headers)
+///     .body(body)
+///     .build_verifier()
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VerifyingKeySet {
+    keys: IndexMap<String, VerifyingKey>,
+}
+
+impl VerifyingKeySet {
+    /// Create an empty key set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse all P-521 keys from JWKs JSON response data.
+    ///
+    /// JWKs with other key types or curves, or that fail to parse, are ignored.
+    ///
+    /// See <https://datatracker.ietf.org/doc/html/rfc7517>.
+    pub fn from_jwks(jwks: &[u8]) -> Result<Self, Error> {
+        let keys = crypto::parse_ec_jwks(jwks)?;
+        Ok(keys
+            .into_iter()
+            .map(|(kid, key)| VerifyingKey::new(kid, key))
+            .collect())
+    }
+
+    /// Add a key, replacing any existing key with the same `kid`.
+    pub fn insert(&mut self, key: VerifyingKey) {
+        self.keys.insert(key.kid.clone(), key);
+    }
+
+    /// Add a key, replacing any existing key with the same `kid`.
+    pub fn with_key(mut self, key: VerifyingKey) -> Self {
+        self.insert(key);
+        self
+    }
+
+    /// Parse & add a P-521 public key pem with the given `kid`.
+    pub fn with_pem(self, kid: impl Into<String>, public_key_pem: &[u8]) -> Result<Self, Error> {
+        Ok(self.with_key(VerifyingKey::from_pem(kid, public_key_pem)?))
+    }
+
+    /// Returns the key for the given `kid`.
+    pub fn get(&self, kid: &str) -> Option<&VerifyingKey> {
+        self.keys.get(kid)
+    }
+
+    /// Iterate over all keys.
+    pub fn iter(&self) -> impl Iterator<Item = &VerifyingKey> {
+        self.keys.values()
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Serialise a JWKs json document, e.g. to serve from a `.well-known/jwks` endpoint.
+    ///
+    /// Keys appear in the order their `kid` was first added. Key coordinates are
+    /// serialised as 66-byte left-padded `x` & `y` values, which may be parsed by
+    /// [`verify_with_jwks`](crate::verify_with_jwks) & [`VerifyingKeySet::from_jwks`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), truelayer_signing::Error> {
+    /// # let (public_key, next_public_key, signing_key): (&[u8], &[u8], truelayer_signing::SigningKey) = unimplemented!();
+    /// let jwks = truelayer_signing::VerifyingKeySet::new()
+    ///     .with_pem("45fc75cf-5649-4134-84b3-192c2c78e990", public_key)?
+    ///     .with_pem("0a1c8fa3-4e2f-4a87-9d3e-51b6c2e4f0d1", next_public_key)?
+    ///     .with_key(signing_key.verifying_key()?)
+    ///     .to_jwks()?;
+    /// // {"keys":[{"alg":"ES512","crv":"P-521","kid":"45fc75cf-...","kty":"EC","x":"...","y":"..."},...]}
+    /// # Ok(()) }
+    /// ```
+    pub fn to_jwks(&self) -> Result<String, Error> {
+        let keys = self
+            .iter()
+            .map(VerifyingKey::to_jwk)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(serde_json::json!({ "keys": keys }).to_string())
+    }
+}
+
+impl From<VerifyingKey> for VerifyingKeySet {
+    fn from(key: VerifyingKey) -> Self {
+        Self::new().with_key(key)
+    }
+}
+
+impl FromIterator<VerifyingKey> for VerifyingKeySet {
+    fn from_iter<T: IntoIterator<Item = VerifyingKey>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<VerifyingKey> for VerifyingKeySet {
+    fn extend<T: IntoIterator<Item = VerifyingKey>>(&mut self, iter: T) {
+        self.keys
+            .extend(iter.into_iter().map(|k| (k.kid.clone(), k)));
+    }
+}
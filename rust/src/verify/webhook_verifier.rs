@@ -0,0 +1,112 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{http::HeaderName, Error, VerifiedSignature};
+
+use super::Verifier;
+
+/// Webhook timestamp header, must be included in webhook signatures.
+pub(crate) const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Tl-Webhook-Timestamp";
+
+const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// Verify a webhook `Tl-Signature` header value, additionally requiring the
+/// signed `X-Tl-Webhook-Timestamp` to be within a tolerance of the current time.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let (jwks, body, tl_signature) = unimplemented!();
+/// # let headers: Vec<(&str, &[u8])> = unimplemented!();
+/// truelayer_signing::verify_with_jwks(jwks)
+///     .method(truelayer_signing::Method::Post)
+///     .path("/webhook")
+///     .headers(headers)
+///     .body(body)
+///     .build_webhook_verifier()
+///     .tolerance(std::time::Duration::from_secs(60))
+///     .verify(tl_signature)?;
+/// # Ok(()) }
+/// ```
+pub struct WebhookVerifier<'a> {
+    pub(crate) base: Verifier<'a>,
+    pub(crate) tolerance: Duration,
+    pub(crate) clock: Box<dyn Fn() -> SystemTime + Send + Sync + 'a>,
+}
+
+/// Debug does not display key info.
+impl fmt::Debug for WebhookVerifier<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "WebhookVerifier")
+    }
+}
+
+impl<'a> WebhookVerifier<'a> {
+    pub(crate) fn new(base: Verifier<'a>) -> Self {
+        Self {
+            base,
+            tolerance: DEFAULT_TOLERANCE,
+            clock: Box::new(SystemTime::now),
+        }
+    }
+
+    /// Sets the maximum allowed difference between the webhook timestamp
+    /// & the current time, in either direction.
+    ///
+    /// Default 5 minutes.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the source of the current time, [`SystemTime::now`] by default.
+    pub fn clock(mut self, clock: impl Fn() -> SystemTime + Send + Sync + 'a) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Verify the given webhook `Tl-Signature` header value.
+    ///
+    /// Returns `Err(_)` if the signature verification fails, or the signed
+    /// `X-Tl-Webhook-Timestamp` is outside the tolerance.
+    pub fn verify(self, tl_signature: &'a str) -> Result<VerifiedSignature, Error> {
+        let Self {
+            base,
+            tolerance,
+            clock,
+        } = self;
+
+        let timestamp = base
+            .base
+            .headers
+            .get(&HeaderName::new(WEBHOOK_TIMESTAMP_HEADER))
+            .cloned();
+
+        // only record the request with the replay guard once the timestamp is checked
+        let (verified, replay_key) = base.verify_unrecorded(tl_signature)?;
+
+        // required header, so will be set if verification succeeded
+        let timestamp = timestamp
+            .ok_or_else(|| Error::MissingRequiredHeader(WEBHOOK_TIMESTAMP_HEADER.into()))?;
+        let timestamp = std::str::from_utf8(&timestamp)
+            .map_err(|e| Error::InvalidWebhookTimestamp(e.into()))?;
+        let signed_at: SystemTime = OffsetDateTime::parse(timestamp, &Rfc3339)
+            .map_err(|e| Error::InvalidWebhookTimestamp(e.into()))?
+            .into();
+
+        let skew = match clock().duration_since(signed_at) {
+            Ok(skew) => skew,
+            Err(err) => err.duration(),
+        };
+        if skew > tolerance {
+            return Err(Error::WebhookTimestampOutOfTolerance(timestamp.to_owned()));
+        }
+
+        base.base.record_replay(replay_key)?;
+        Ok(verified)
+    }
+}
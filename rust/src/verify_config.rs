@@ -0,0 +1,260 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    verify::ReplayCheck, AsyncReplayGuard, Error, Method, ReplayKey, TlVersion, TrailingSlash,
+    VerifiedSignature, VerifyingKeySet, VersionPolicy,
+};
+
+/// `Tl-Signature` header name.
+#[cfg(any(feature = "http", feature = "actix-web"))]
+const TL_SIGNATURE: &str = "Tl-Signature";
+
+/// Key material, required headers, trailing slash & replay policy used to verify inbound requests.
+///
+/// Used by [`RequestVerifier`](crate::RequestVerifier) & the framework integrations, e.g.
+/// `VerifySignatureLayer`, `VerifiedBody` & `ActixVerifySignature`.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), truelayer_signing::Error> {
+/// # let public_key: &[u8] = unimplemented!();
+/// let keys = truelayer_signing::VerifyingKeySet::new()
+///     .with_pem("45fc75cf-5649-4134-84b3-192c2c78e990", public_key)?;
+///
+/// let config = truelayer_signing::VerifyConfig::new(keys).require_header("Idempotency-Key");
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifyConfig {
+    keys: KeySource,
+    required_headers: Vec<String>,
+    trailing_slash: TrailingSlash,
+    replay_guard: Option<ReplayCheck<'static>>,
+}
+
+impl VerifyConfig {
+    /// Verify requests using static keys, the key matching the signature `kid` will be used.
+    pub fn new(keys: impl Into<Arc<VerifyingKeySet>>) -> Self {
+        Self::with_key_source(KeySource::Static(keys.into()))
+    }
+
+    /// Verify requests using keys from the jwks of each signature's `jku`.
+    #[cfg(feature = "jwks-client")]
+    pub fn jwks(provider: crate::JwksProvider) -> Self {
+        Self::with_key_source(KeySource::Jwks(provider))
+    }
+
+    fn with_key_source(keys: KeySource) -> Self {
+        Self {
+            keys,
+            required_headers: vec![],
+            trailing_slash: TrailingSlash::default(),
+            replay_guard: None,
+        }
+    }
+
+    /// Require a header name that must be included in the `Tl-Signature`.
+    /// May be called multiple times to add multiple required headers.
+    pub fn require_header(mut self, key: impl Into<String>) -> Self {
+        self.required_headers.push(key.into());
+        self
+    }
+
+    /// Sets how signatures of the request path with, or without, a trailing slash
+    /// are treated, default [`TrailingSlash::Tolerant`].
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    /// Reject signatures that have already been successfully verified, as
+    /// identified by `key`, using the given [`AsyncReplayGuard`]. Verified keys are
+    /// remembered for `ttl`.
+    ///
+    /// [`ReplayKey::Header`] names are required to be included in the `Tl-Signature`.
+    pub fn replay_guard(
+        mut self,
+        guard: Arc<impl AsyncReplayGuard + 'static>,
+        key: ReplayKey<'_>,
+        ttl: Duration,
+    ) -> Self {
+        let replay_guard = ReplayCheck::new_async(guard, key, ttl).into_owned();
+        if let Some(header) = replay_guard.header() {
+            self.required_headers.push(header.to_string());
+        }
+        self.replay_guard = Some(replay_guard);
+        self
+    }
+
+    /// Verify the `Tl-Signature` of a request, using all request headers
+    /// & the buffered body.
+    #[cfg(any(feature = "http", feature = "actix-web"))]
+    pub(crate) async fn verify<'a>(
+        &self,
+        method: &str,
+        path: &str,
+        headers: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+        body: &[u8],
+    ) -> Result<VerifiedSignature, Error> {
+        let headers: Vec<_> = headers.into_iter().collect();
+        let tl_signature = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(TL_SIGNATURE))
+            .ok_or_else(|| Error::malformed_signature("missing Tl-Signature header"))?
+            .1;
+        let tl_signature =
+            std::str::from_utf8(tl_signature).map_err(|e| Error::MalformedSignature {
+                reason: "invalid Tl-Signature header value",
+                source: Some(e.into()),
+            })?;
+
+        self.verify_signature(
+            Method::from_name(method)?,
+            path,
+            headers,
+            body,
+            tl_signature,
+            VersionPolicy::V2,
+        )
+        .await
+    }
+
+    /// Verify the given `Tl-Signature` header value against a request.
+    pub(crate) async fn verify_signature(
+        &self,
+        method: Method,
+        path: &str,
+        headers: Vec<(&str, &[u8])>,
+        body: &[u8],
+        tl_signature: &str,
+        version_policy: VersionPolicy,
+    ) -> Result<VerifiedSignature, Error> {
+        check_signed_headers_unique(&headers, tl_signature)?;
+        let keys = self.keys.keys(tl_signature).await?;
+        let verifier = crate::verify_with_key_set(&keys)
+            .method(method)
+            .path(path)
+            .headers(headers)
+            .trailing_slash(self.trailing_slash);
+        let mut verifier = self
+            .required_headers
+            .iter()
+            .fold(verifier, |verifier, header| verifier.require_header(header));
+        if let Some(replay_guard) = &self.replay_guard {
+            verifier = verifier.replay_check(replay_guard.clone());
+        }
+        let verifier = verifier.body(body).build_verifier();
+
+        let (verified, replay_key) = match version_policy {
+            VersionPolicy::V2 => verifier.verify_unrecorded(tl_signature)?,
+            // v1 signatures do not include headers, so cannot satisfy required headers
+            VersionPolicy::V1OrV2 => match self.required_headers.first() {
+                Some(header) if is_v1(tl_signature) => {
+                    return Err(Error::MissingRequiredHeader(header.clone()));
+                }
+                _ => verifier.verify_v1_or_v2_unrecorded(tl_signature)?,
+            },
+        };
+        verifier.record_replay_async(replay_key).await?;
+        Ok(verified)
+    }
+}
+
+/// Fails with [`Error::RepeatedHeader`] if a header declared in the signature `tl_headers`
+/// has multiple values, rather than letting one of the values silently win.
+pub(crate) fn check_signed_headers_unique(
+    headers: &[(&str, &[u8])],
+    tl_signature: &str,
+) -> Result<(), Error> {
+    // unparseable signatures are rejected by verification
+    let Some(tl_headers) = crate::extract_jws_header(tl_signature)
+        .ok()
+        .and_then(|header| header.tl_headers)
+    else {
+        return Ok(());
+    };
+    for signed in tl_headers.split(',').filter(|h| !h.is_empty()) {
+        let values = headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(signed))
+            .count();
+        if values > 1 {
+            return Err(Error::RepeatedHeader(signed.to_owned()));
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the `Tl-Signature` is a v1, body only, signature.
+fn is_v1(tl_signature: &str) -> bool {
+    crate::extract_jws_header(tl_signature)
+        .is_ok_and(|header| matches!(header.tl_version, None | Some(TlVersion::V1)))
+}
+
+/// Source of the keys used to verify inbound requests.
+#[derive(Debug, Clone)]
+enum KeySource {
+    /// Static pre-parsed keys.
+    Static(Arc<VerifyingKeySet>),
+    /// Keys fetched from the signature's `jku`.
+    #[cfg(feature = "jwks-client")]
+    Jwks(crate::JwksProvider),
+}
+
+impl KeySource {
+    /// Returns the keys to verify the given `Tl-Signature`.
+    #[cfg_attr(not(feature = "jwks-client"), allow(unused_variables))]
+    async fn keys(&self, tl_signature: &str) -> Result<Arc<VerifyingKeySet>, Error> {
+        match self {
+            KeySource::Static(keys) => Ok(keys.clone()),
+            #[cfg(feature = "jwks-client")]
+            KeySource::Jwks(provider) => provider.keys_for_signature(tl_signature).await,
+        }
+    }
+}
+
+/// Stable json `error` code of a verification failure, e.g. `unknown_kid`.
+#[cfg(any(feature = "tower", feature = "actix-web"))]
+pub(crate) fn error_code(err: &Error) -> &'static str {
+    match err {
+        Error::InvalidKey(_) => "invalid_key",
+        Error::UnknownKid(_) => "unknown_kid",
+        Error::MalformedSignature { .. } => "malformed_signature",
+        Error::UnsupportedVersion(_) => "unsupported_version",
+        Error::MissingRequiredHeader(_) => "missing_required_header",
+        Error::DeclaredHeaderAbsent(_) => "declared_header_absent",
+        Error::RepeatedHeader(_) => "repeated_header",
+        Error::InvalidSignature(_) => "invalid_signature",
+        Error::InvalidWebhookTimestamp(_) => "invalid_webhook_timestamp",
+        Error::WebhookTimestampOutOfTolerance(_) => "webhook_timestamp_out_of_tolerance",
+        Error::Replayed => "replayed",
+        Error::ReplayGuardFailed(_) => "replay_guard_failed",
+        Error::UnpermittedJku(_) => "unpermitted_jku",
+        Error::JwksFetch(_) => "jwks_fetch_failed",
+        Error::UnsupportedMethod(_) => "unsupported_method",
+        Error::SigningFailed(_)
+        | Error::IncorrectPassphrase
+        | Error::KeyGeneration(_)
+        | Error::Other(_) => "verification_failed",
+    }
+}
+
+/// Http status of a verification failure rejection, `401 Unauthorized` unless verification
+/// failed due to a server-side problem, e.g. the jwks could not be fetched.
+#[cfg(any(feature = "tower", feature = "actix-web"))]
+pub(crate) fn rejection_status(err: &Error) -> u16 {
+    match err {
+        // 502 Bad Gateway
+        Error::JwksFetch(_) => 502,
+        // 503 Service Unavailable
+        Error::ReplayGuardFailed(_) => 503,
+        _ => 401,
+    }
+}
+
+/// Json rejection response body, e.g.
+/// `{"error":"invalid_signature","reason":"signature verification failed"}`.
+#[cfg(any(feature = "tower", feature = "actix-web"))]
+pub(crate) fn rejection_body(error: &str, reason: &str) -> String {
+    serde_json::json!({ "error": error, "reason": reason }).to_string()
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "actix-web")]
+
+use actix_web::{
+    http::StatusCode,
+    test::{self, TestRequest},
+    web, App, HttpMessage, HttpRequest, HttpResponse,
+};
+use truelayer_signing::{
+    ActixVerifySignature, Method, SigningKey, VerifiedSignature, VerifyConfig, VerifyingKeySet,
+};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+const PATH: &str = "/merchant_accounts/a61acaef/sweeping";
+const IDEMPOTENCY_KEY: &str = "idemp-2076717c-9005-4811-a321-9e0787fa0382";
+const BODY: &[u8] = br#"{"currency":"GBP","max_amount_in_minor":5000000}"#;
+
+fn config() -> VerifyConfig {
+    let keys = VerifyingKeySet::new()
+        .with_pem(KID, PUBLIC_KEY)
+        .expect("verifying key");
+    VerifyConfig::new(keys)
+}
+
+fn middleware() -> ActixVerifySignature {
+    ActixVerifySignature::new(config())
+}
+
+fn tl_signature(signed_headers: &[(&str, &[u8])], body: &[u8]) -> String {
+    let signing_key = SigningKey::from_pem(KID, PRIVATE_KEY).expect("signing key");
+    truelayer_signing::sign_with_key(&signing_key)
+        .method(Method::Post)
+        .path(PATH)
+        .headers(signed_headers.iter().copied())
+        .body(body)
+        .build_signer()
+        .sign()
+        .expect("sign")
+}
+
+/// Call an echo handler wrapped by `middleware`, returning the status & response body.
+async fn call(middleware: ActixVerifySignature, request: TestRequest) -> (StatusCode, web::Bytes) {
+    let app = test::init_service(
+        App::new().service(
+            web::resource(PATH)
+                .wrap(middleware)
+                .route(web::post().to(|body: web::Bytes| async move { body })),
+        ),
+    )
+    .await;
+    let response = test::call_service(&app, request.uri(PATH).to_request()).await;
+    let status = response.status();
+    (status, test::read_body(response).await)
+}
+
+#[actix_web::test]
+async fn verified_request_passes_payload() {
+    let tl_signature = tl_signature(&[("Idempotency-Key", IDEMPOTENCY_KEY.as_bytes())], BODY);
+    let request = TestRequest::post()
+        .insert_header(("Idempotency-Key", IDEMPOTENCY_KEY))
+        .insert_header(("Tl-Signature", tl_signature))
+        .set_payload(BODY);
+
+    let (status, body) = call(middleware(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, BODY);
+}
+
+#[actix_web::test]
+async fn modified_header_rejected() {
+    let tl_signature = tl_signature(&[("Idempotency-Key", IDEMPOTENCY_KEY.as_bytes())], BODY);
+    let request = TestRequest::post()
+        .insert_header(("Idempotency-Key", "idemp-different"))
+        .insert_header(("Tl-Signature", tl_signature))
+        .set_payload(BODY);
+
+    let (status, body) = call(middleware(), request).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "invalid_signature");
+    assert_eq!(body["reason"], "signature verification failed");
+}
+
+#[actix_web::test]
+async fn required_header() {
+    let request = || {
+        TestRequest::post()
+            .insert_header(("Idempotency-Key", IDEMPOTENCY_KEY))
+            .insert_header(("Tl-Signature", tl_signature(&[], BODY)))
+            .set_payload(BODY)
+    };
+
+    let (status, _) = call(middleware(), request()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let middleware = ActixVerifySignature::new(config().require_header("Idempotency-Key"));
+    let (status, body) = call(middleware, request()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "missing_required_header");
+}
+
+#[actix_web::test]
+async fn custom_error_handler() {
+    let middleware = middleware().error_handler(|_| HttpResponse::Forbidden().finish());
+
+    let request = TestRequest::post().set_payload(BODY);
+    let (status, body) = call(middleware, request).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(body.is_empty());
+}
+
+#[actix_web::test]
+async fn verified_signature_extension() {
+    let tl_signature = tl_signature(&[("Idempotency-Key", IDEMPOTENCY_KEY.as_bytes())], BODY);
+    let app = test::init_service(
+        App::new().service(web::resource(PATH).wrap(middleware()).route(web::post().to(
+            |request: HttpRequest| async move {
+                let extensions = request.extensions();
+                let verified = extensions.get::<VerifiedSignature>().expect("extension");
+                HttpResponse::Ok().body(verified.signed_headers.join(","))
+            },
+        ))),
+    )
+    .await;
+    let request = TestRequest::post()
+        .uri(PATH)
+        .insert_header(("Idempotency-Key", IDEMPOTENCY_KEY))
+        .insert_header(("Tl-Signature", tl_signature))
+        .set_payload(BODY)
+        .to_request();
+
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(test::read_body(response).await, "Idempotency-Key");
+}
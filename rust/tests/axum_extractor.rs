@@ -0,0 +1,111 @@
+#![cfg(feature = "axum")]
+
+use axum::{
+    body::{Body, Bytes},
+    http::{Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+use truelayer_signing::{SigningKey, VerifiedBody, VerifyConfig, VerifyingKeySet};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+const BODY: &[u8] =
+    br#"{"event_type":"example","event_id":"18b2842b-a57b-4887-a0a6-d3c7c36f1020"}"#;
+
+#[derive(serde::Deserialize)]
+struct Webhook {
+    event_type: String,
+}
+
+fn app(config: VerifyConfig) -> Router {
+    Router::new()
+        .route(
+            "/hook/bytes",
+            post(|VerifiedBody(body): VerifiedBody| async move { body }),
+        )
+        .route(
+            "/hook/json",
+            post(
+                |VerifiedBody(Json(hook)): VerifiedBody<Json<Webhook>>| async move {
+                    hook.event_type
+                },
+            ),
+        )
+        .with_state(config)
+}
+
+fn config() -> VerifyConfig {
+    let keys = VerifyingKeySet::new()
+        .with_pem(KID, PUBLIC_KEY)
+        .expect("verifying key");
+    VerifyConfig::new(keys)
+}
+
+fn signed_request(path: &str, body: &[u8]) -> Request<Body> {
+    let signing_key = SigningKey::from_pem(KID, PRIVATE_KEY).expect("signing key");
+    let mut request = Request::post(path)
+        .header("Content-Type", "application/json")
+        .header("X-Tl-Webhook-Timestamp", "2021-11-29T11:42:55Z")
+        .body(body.to_vec())
+        .unwrap();
+    truelayer_signing::sign_request(&signing_key, &mut request, &["X-Tl-Webhook-Timestamp"])
+        .expect("sign_request");
+    request.map(Body::from)
+}
+
+async fn call(app: Router, request: Request<Body>) -> (StatusCode, Bytes) {
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body)
+}
+
+#[tokio::test]
+async fn verified_bytes() {
+    let (status, body) = call(app(config()), signed_request("/hook/bytes", BODY)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, BODY);
+}
+
+#[tokio::test]
+async fn verified_json() {
+    let (status, body) = call(app(config()), signed_request("/hook/json", BODY)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "example");
+}
+
+#[tokio::test]
+async fn invalid_signature_rejected() {
+    // signed for a different path
+    let mut request = signed_request("/hook/bytes", BODY);
+    *request.uri_mut() = "/hook/json".parse().unwrap();
+
+    let (status, body) = call(app(config()), request).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "invalid_signature");
+}
+
+#[tokio::test]
+async fn required_header() {
+    let config = config().require_header("Idempotency-Key");
+
+    let (status, body) = call(app(config), signed_request("/hook/bytes", BODY)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "missing_required_header");
+    assert_eq!(
+        body["reason"],
+        "signature is missing required header Idempotency-Key"
+    );
+}
+
+#[tokio::test]
+async fn invalid_json_after_verification() {
+    let (status, _) = call(app(config()), signed_request("/hook/json", b"[]")).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,229 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+const PRIVATE_KEY: &str = "../test-resources/ec512-private.pem";
+const PUBLIC_KEY: &str = "../test-resources/ec512-public.pem";
+const TL_SIGNATURE: &str = include_str!("../../test-resources/tl-signature.txt");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+const PATH: &str = "/merchant_accounts/a61acaef-ee05-4077-92f3-25543a11bd8d/sweeping";
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key: idemp-2076717c-9005-4811-a321-9e0787fa0382";
+const BODY: &[u8] = br#"{"currency":"GBP","max_amount_in_minor":5000000,"name":"Foo???"}"#;
+
+fn tl_signing(args: &[&str]) -> Output {
+    tl_signing_stdin(args, b"")
+}
+
+fn tl_signing_stdin(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tl-signing"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("run tl-signing");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().expect("tl-signing output")
+}
+
+fn stdout_json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).expect("json stdout")
+}
+
+#[test]
+fn sign_then_verify() {
+    let output = tl_signing(&[
+        "sign",
+        "--kid",
+        KID,
+        "--key",
+        PRIVATE_KEY,
+        "--method",
+        "post",
+        "--path",
+        PATH,
+        "-H",
+        IDEMPOTENCY_KEY,
+    ]);
+    assert!(output.status.success(), "{output:?}");
+    let tl_signature = String::from_utf8(output.stdout).unwrap();
+
+    let verify = |path: &str, extra_args: &[&str]| {
+        let args = [
+            "verify",
+            "--pem",
+            PUBLIC_KEY,
+            "--signature",
+            tl_signature.trim(),
+            "--require-header",
+            "Idempotency-Key",
+            "--method",
+            "POST",
+            "--path",
+            path,
+            "-H",
+            IDEMPOTENCY_KEY,
+            "--json",
+        ];
+        tl_signing(&[&args[..], extra_args].concat())
+    };
+
+    let output = verify(PATH, &[]);
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+    let output = stdout_json(&output);
+    assert_eq!(output["valid"], true);
+    assert_eq!(output["kid"], KID);
+    assert_eq!(
+        output["signed_headers"],
+        serde_json::json!(["Idempotency-Key"])
+    );
+    assert_eq!(output["signed_path"], serde_json::Value::Null);
+
+    let trailing_slash_path = format!("{PATH}/");
+    let output = verify(&trailing_slash_path, &[]);
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+    assert_eq!(stdout_json(&output)["signed_path"], PATH);
+
+    let output = verify(&trailing_slash_path, &["--strict-trailing-slash"]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+
+    let output = verify("/foo", &[]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let output = stdout_json(&output);
+    assert_eq!(output["valid"], false);
+    assert_eq!(output["reason"], "signature verification failed");
+    assert!(
+        output["signing_payload"]
+            .as_str()
+            .unwrap()
+            .starts_with("POST /foo\nIdempotency-Key: "),
+        "{output}"
+    );
+}
+
+#[test]
+fn verify_static_signature_with_jwks() {
+    let args = [
+        "verify",
+        "--jwks",
+        "../test-resources/jwks.json",
+        "--signature",
+        TL_SIGNATURE.trim(),
+        "--method",
+        "POST",
+        "--path",
+        PATH,
+        "-H",
+        "X-Whatever-2: t2345d",
+        "-H",
+        IDEMPOTENCY_KEY,
+        "--body",
+        "-",
+    ];
+
+    let output = tl_signing_stdin(&args, BODY);
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "valid");
+
+    let output = tl_signing_stdin(&args, b"{}");
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+}
+
+#[test]
+fn inspect_json() {
+    let output = tl_signing(&["inspect", TL_SIGNATURE.trim(), "--json"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = stdout_json(&output);
+    assert_eq!(output["header"]["alg"], "ES512");
+    assert_eq!(output["header"]["kid"], KID);
+    assert_eq!(output["header"]["tl_version"], "2");
+    assert_eq!(
+        output["signed_headers"],
+        serde_json::json!(["Idempotency-Key"])
+    );
+    assert_eq!(output["signing_payload"], serde_json::Value::Null);
+}
+
+#[test]
+fn inspect_signing_payload() {
+    let args = [
+        "inspect",
+        TL_SIGNATURE.trim(),
+        "--method",
+        "POST",
+        "--path",
+        PATH,
+        "-H",
+        "X-Whatever-2: t2345d",
+        "-H",
+        IDEMPOTENCY_KEY,
+        "--body",
+        "-",
+        "--json",
+    ];
+    let output = tl_signing_stdin(&args, BODY);
+    assert!(output.status.success(), "{output:?}");
+
+    let payload = stdout_json(&output)["signing_payload"]
+        .as_str()
+        .expect("signing_payload")
+        .to_owned();
+    assert_eq!(
+        payload,
+        format!(
+            "POST {PATH}\nIdempotency-Key: idemp-2076717c-9005-4811-a321-9e0787fa0382\n{}",
+            std::str::from_utf8(BODY).unwrap()
+        )
+    );
+}
+
+#[test]
+fn invalid_key_file_errors() {
+    let output = tl_signing(&[
+        "sign",
+        "--kid",
+        KID,
+        "--key",
+        "missing.pem",
+        "--method",
+        "POST",
+        "--path",
+        PATH,
+    ]);
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+}
+
+#[test]
+fn invalid_path_errors() {
+    let sign = [
+        "sign",
+        "--kid",
+        KID,
+        "--key",
+        PRIVATE_KEY,
+        "--method",
+        "POST",
+    ];
+    let inspect = ["inspect", TL_SIGNATURE.trim(), "--method", "POST"];
+
+    for args in [&sign[..], &inspect[..]] {
+        let output = tl_signing(&[args, &["--path", "payouts", "--json"]].concat());
+        assert_eq!(output.status.code(), Some(2), "{output:?}");
+        assert_eq!(
+            stdout_json(&output)["error"],
+            "invalid value 'payouts' for '--path <PATH>': path must start with '/'"
+        );
+    }
+
+    let output = tl_signing(&[&sign[..], &["--path", "payouts"]].concat());
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("path must start with '/'"),
+        "{output:?}"
+    );
+}
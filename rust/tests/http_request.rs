@@ -0,0 +1,201 @@
+#![cfg(feature = "http")]
+
+use truelayer_signing::{Error, Method, SigningKey, TrailingSlash, VerifyConfig, VerifyingKeySet};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+
+fn keys() -> (SigningKey, VerifyingKeySet) {
+    let signing_key = SigningKey::from_pem(KID, PRIVATE_KEY).expect("signing key");
+    let keys = VerifyingKeySet::new()
+        .with_pem(KID, PUBLIC_KEY)
+        .expect("verifying key");
+    (signing_key, keys)
+}
+
+fn payout_request() -> http::Request<Vec<u8>> {
+    http::Request::post("https://api.truelayer.com/merchant_accounts/a61acaef/sweeping?q=1")
+        .header(
+            "Idempotency-Key",
+            "idemp-2076717c-9005-4811-a321-9e0787fa0382",
+        )
+        .header("X-Whatever", "aoitbeh")
+        .body(br#"{"currency":"GBP","max_amount_in_minor":5000000}"#.to_vec())
+        .unwrap()
+}
+
+#[test]
+fn sign_and_verify_request() {
+    let (signing_key, keys) = keys();
+    let mut request = payout_request();
+
+    truelayer_signing::sign_request(&signing_key, &mut request, &["Idempotency-Key"])
+        .expect("sign_request");
+
+    let tl_signature = request.headers()["Tl-Signature"].to_str().unwrap();
+    let jws_header = truelayer_signing::extract_jws_header(tl_signature).unwrap();
+    assert_eq!(jws_header.tl_headers.as_deref(), Some("Idempotency-Key"));
+
+    // verifiable using the path without the query
+    truelayer_signing::verify_with_key_set(&keys)
+        .method(Method::Post)
+        .path("/merchant_accounts/a61acaef/sweeping")
+        .header(
+            "Idempotency-Key",
+            b"idemp-2076717c-9005-4811-a321-9e0787fa0382",
+        )
+        .body(request.body())
+        .build_verifier()
+        .verify(tl_signature)
+        .expect("verify");
+
+    truelayer_signing::verify_request(&keys, &request).expect("verify_request");
+}
+
+#[test]
+fn verify_request_modified() {
+    let (signing_key, keys) = keys();
+    let mut request = payout_request();
+    truelayer_signing::sign_request(&signing_key, &mut request, &["Idempotency-Key"])
+        .expect("sign_request");
+
+    let mut modified = request.clone();
+    modified.headers_mut().insert(
+        "Idempotency-Key",
+        http::HeaderValue::from_static("idemp-different"),
+    );
+    truelayer_signing::verify_request(&keys, &modified).expect_err("modified header");
+
+    let mut modified = request.clone();
+    *modified.method_mut() = http::Method::PUT;
+    truelayer_signing::verify_request(&keys, &modified).expect_err("modified method");
+
+    let mut modified = request;
+    modified.body_mut().push(b' ');
+    truelayer_signing::verify_request(&keys, &modified).expect_err("modified body");
+}
+
+#[test]
+fn verify_request_missing_signature() {
+    let (_, keys) = keys();
+    let error = truelayer_signing::verify_request(&keys, &payout_request())
+        .expect_err("no Tl-Signature header");
+    assert!(
+        matches!(error, Error::MalformedSignature { .. }),
+        "{error:?}"
+    );
+}
+
+/// Repeated values of a signed header are rejected rather than one value silently winning.
+#[test]
+fn verify_request_repeated_signed_header() {
+    let (signing_key, keys) = keys();
+    let mut request = payout_request();
+    truelayer_signing::sign_request(&signing_key, &mut request, &["Idempotency-Key"])
+        .expect("sign_request");
+
+    request.headers_mut().append(
+        "Idempotency-Key",
+        http::HeaderValue::from_static("idemp-different"),
+    );
+    let error = truelayer_signing::verify_request(&keys, &request).expect_err("repeated header");
+    assert!(
+        matches!(&error, Error::RepeatedHeader(h) if h == "Idempotency-Key"),
+        "{error:?}"
+    );
+}
+
+/// Repeated values of headers not included in the signature are ignored.
+#[test]
+fn verify_request_repeated_unsigned_header() {
+    let (signing_key, keys) = keys();
+    let mut request = payout_request();
+    truelayer_signing::sign_request(&signing_key, &mut request, &["Idempotency-Key"])
+        .expect("sign_request");
+
+    request
+        .headers_mut()
+        .append("X-Whatever", http::HeaderValue::from_static("again"));
+    truelayer_signing::verify_request(&keys, &request).expect("verify_request");
+}
+
+#[tokio::test]
+async fn verify_request_parts_config() {
+    let (signing_key, keys) = keys();
+    let config = VerifyConfig::new(keys).require_header("Idempotency-Key");
+    let signed = |path: &str, signed_headers: &[&str]| {
+        let mut request = payout_request();
+        *request.uri_mut() = path.parse().unwrap();
+        truelayer_signing::sign_request(&signing_key, &mut request, signed_headers)
+            .expect("sign_request");
+        request.into_parts()
+    };
+
+    let (parts, body) = signed("/payouts", &["Idempotency-Key"]);
+    let verified = truelayer_signing::verify_request_parts(&parts, &body, &config)
+        .await
+        .expect("verify_request_parts");
+    assert_eq!(verified.signed_headers, ["Idempotency-Key"]);
+
+    // required headers
+    let (parts, body) = signed("/payouts", &[]);
+    let error = truelayer_signing::verify_request_parts(&parts, &body, &config)
+        .await
+        .expect_err("Idempotency-Key is not signed");
+    assert!(
+        matches!(&error, Error::MissingRequiredHeader(h) if h == "Idempotency-Key"),
+        "{error:?}"
+    );
+
+    // trailing slash
+    let (mut parts, body) = signed("/payouts/", &["Idempotency-Key"]);
+    parts.uri = "/payouts".parse().unwrap();
+    truelayer_signing::verify_request_parts(&parts, &body, &config)
+        .await
+        .expect("tolerant verify");
+    let strict = config.clone().trailing_slash(TrailingSlash::Strict);
+    let error = truelayer_signing::verify_request_parts(&parts, &body, &strict)
+        .await
+        .expect_err("strict verify");
+    assert!(matches!(error, Error::InvalidSignature(_)), "{error:?}");
+
+    // repeated signed header
+    let (mut parts, body) = signed("/payouts", &["Idempotency-Key"]);
+    parts.headers.append(
+        "idempotency-key",
+        http::HeaderValue::from_static("idemp-different"),
+    );
+    let error = truelayer_signing::verify_request_parts(&parts, &body, &config)
+        .await
+        .expect_err("repeated header");
+    assert!(matches!(error, Error::RepeatedHeader(_)), "{error:?}");
+}
+
+#[test]
+fn sign_request_missing_header() {
+    let (signing_key, _) = keys();
+    let mut request = payout_request();
+    let error = truelayer_signing::sign_request(&signing_key, &mut request, &["X-Missing"])
+        .expect_err("missing header");
+    assert!(
+        matches!(&error, Error::MissingRequiredHeader(h) if h == "X-Missing"),
+        "{error:?}"
+    );
+    assert!(request.headers().get("Tl-Signature").is_none());
+}
+
+#[test]
+fn method_conversion() {
+    assert!(matches!(
+        Method::try_from(&http::Method::PATCH),
+        Ok(Method::Patch)
+    ));
+    assert_eq!(http::Method::from(Method::Delete), http::Method::DELETE);
+
+    let error = Method::try_from(http::Method::OPTIONS).expect_err("unsupported");
+    assert!(
+        matches!(&error, Error::UnsupportedMethod(m) if m == "OPTIONS"),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,257 @@
+#![cfg(feature = "jwks-client")]
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "jwks-client-blocking")]
+use truelayer_signing::BlockingJwksProvider;
+use truelayer_signing::{Error, JwksProvider, Method};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const JWKS: &str = include_str!("../../test-resources/jwks.json");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+/// Jwks without the `KID` P-521 key.
+const RSA_ONLY_JWKS: &str = r#"{"keys":[{"kty":"RSA","alg":"RS512","kid":"db2e0bb4-e33d-4fc6-a8c5-645e70331127","n":"piC9","e":"AQAB"}]}"#;
+
+/// Local http stand-in for a jwks endpoint.
+struct JwksServer {
+    jku: String,
+    response: Arc<Mutex<(Option<&'static str>, &'static str)>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl JwksServer {
+    /// Serve `jwks` with an optional `Cache-Control` header value.
+    fn start(cache_control: Option<&'static str>, jwks: &'static str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let jku = format!("http://{}/.well-known/jwks", listener.local_addr().unwrap());
+        let response = Arc::new(Mutex::new((cache_control, jwks)));
+        let hits = Arc::new(AtomicUsize::new(0));
+
+        let (server_response, server_hits) = (response.clone(), hits.clone());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&mut stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                server_hits.fetch_add(1, Ordering::SeqCst);
+
+                let (cache_control, jwks) = *server_response.lock().unwrap();
+                let cache_control = cache_control
+                    .map(|v| format!("Cache-Control: {v}\r\n"))
+                    .unwrap_or_default();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{cache_control}\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{jwks}",
+                    jwks.len(),
+                );
+            }
+        });
+
+        Self {
+            jku,
+            response,
+            hits,
+        }
+    }
+
+    fn set_jwks(&self, jwks: &'static str) {
+        self.response.lock().unwrap().1 = jwks;
+    }
+
+    fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
+
+#[tokio::test]
+async fn caches_according_to_max_age() {
+    let server = JwksServer::start(Some("public, max-age=600"), JWKS);
+    let provider = JwksProvider::new().allowed_jkus([&server.jku]);
+
+    for _ in 0..3 {
+        let keys = provider.keys(&server.jku, KID).await.expect("keys");
+        assert!(keys.get(KID).is_some());
+    }
+    assert_eq!(server.hits(), 1);
+
+    // clones share the cache
+    provider.clone().keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 1);
+}
+
+#[tokio::test]
+async fn no_store_is_not_cached() {
+    let server = JwksServer::start(Some("no-store"), JWKS);
+    let provider = JwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .fallback_max_age(Duration::from_secs(600));
+
+    provider.keys(&server.jku, KID).await.expect("keys");
+    provider.keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn fallback_max_age_without_cache_control() {
+    let server = JwksServer::start(None, JWKS);
+
+    let provider = JwksProvider::new().allowed_jkus([&server.jku]);
+    provider.keys(&server.jku, KID).await.expect("keys");
+    provider.keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 1, "cached by default");
+
+    let provider = JwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .fallback_max_age(Duration::ZERO);
+    provider.keys(&server.jku, KID).await.expect("keys");
+    provider.keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn refetch_on_unknown_kid() {
+    let server = JwksServer::start(Some("max-age=600"), RSA_ONLY_JWKS);
+    let provider = JwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .min_refetch_interval(Duration::ZERO);
+
+    let keys = provider.keys(&server.jku, KID).await.expect("keys");
+    assert!(keys.get(KID).is_none());
+    assert_eq!(server.hits(), 1);
+
+    // key rotated in
+    server.set_jwks(JWKS);
+    let keys = provider.keys(&server.jku, KID).await.expect("keys");
+    assert!(keys.get(KID).is_some());
+    assert_eq!(server.hits(), 2);
+
+    // now cached
+    provider.keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn unknown_kid_refetch_is_rate_limited() {
+    let server = JwksServer::start(Some("max-age=600"), JWKS);
+    let provider = JwksProvider::new().allowed_jkus([&server.jku]);
+
+    provider.keys(&server.jku, KID).await.expect("keys");
+    let keys = provider
+        .keys(&server.jku, "unknown-kid")
+        .await
+        .expect("keys");
+    assert!(keys.get("unknown-kid").is_none());
+    assert_eq!(server.hits(), 1);
+}
+
+#[tokio::test]
+async fn concurrent_fetches_are_shared() {
+    // not cached, so each sequential call fetches
+    let server = JwksServer::start(None, JWKS);
+    let provider = JwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .fallback_max_age(Duration::ZERO);
+
+    let (a, b, c) = tokio::join!(
+        provider.keys(&server.jku, KID),
+        provider.keys(&server.jku, KID),
+        provider.keys(&server.jku, "unknown-kid"),
+    );
+    for keys in [a, b, c] {
+        assert!(keys.expect("keys").get(KID).is_some());
+    }
+    assert_eq!(server.hits(), 1);
+
+    provider.keys(&server.jku, KID).await.expect("keys");
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn unpermitted_jku() {
+    let server = JwksServer::start(Some("max-age=600"), JWKS);
+    let provider = JwksProvider::new();
+
+    let error = provider
+        .keys(&server.jku, KID)
+        .await
+        .expect_err("jku is not allowed");
+    assert!(
+        matches!(&error, Error::UnpermittedJku(jku) if *jku == server.jku),
+        "{error:?}"
+    );
+    assert_eq!(server.hits(), 0);
+
+    // signature with a TrueLayer jku, but not in the allow-list
+    let hook_signature = include_str!("../../test-resources/webhook-signature.txt").trim();
+    JwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .keys_for_signature(hook_signature)
+        .await
+        .expect_err("jku is not allowed");
+    assert_eq!(server.hits(), 0);
+}
+
+#[tokio::test]
+async fn verify_webhook_with_keys_for_signature() {
+    let server = JwksServer::start(Some("max-age=600"), JWKS);
+    let provider = JwksProvider::new().allow_jku(&server.jku);
+
+    let body = br#"{"event_type":"example","event_id":"18b2842b-a57b-4887-a0a6-d3c7c36f1020"}"#;
+    let tl_signature = truelayer_signing::sign_with_pem(KID, PRIVATE_KEY)
+        .jku(&server.jku)
+        .method(Method::Post)
+        .path("/tl-webhook")
+        .header("X-Tl-Webhook-Timestamp", b"2021-11-29T11:42:55Z")
+        .body(body)
+        .build_signer()
+        .sign()
+        .expect("sign");
+
+    let keys = provider
+        .keys_for_signature(&tl_signature)
+        .await
+        .expect("keys_for_signature");
+
+    truelayer_signing::verify_with_key_set(&keys)
+        .method(Method::Post)
+        .path("/tl-webhook")
+        .header("X-Tl-Webhook-Timestamp", b"2021-11-29T11:42:55Z")
+        .body(body)
+        .build_verifier()
+        .verify(&tl_signature)
+        .expect("verify");
+}
+
+#[cfg(feature = "jwks-client-blocking")]
+#[test]
+fn blocking_caches_and_refetches_on_unknown_kid() {
+    let server = JwksServer::start(Some("max-age=600"), RSA_ONLY_JWKS);
+    let provider = BlockingJwksProvider::new()
+        .allowed_jkus([&server.jku])
+        .min_refetch_interval(Duration::ZERO);
+
+    let keys = provider.keys(&server.jku, KID).expect("keys");
+    assert!(keys.get(KID).is_none());
+
+    server.set_jwks(JWKS);
+    let keys = provider.keys(&server.jku, KID).expect("keys");
+    assert!(keys.get(KID).is_some());
+    provider.keys(&server.jku, KID).expect("keys");
+    assert_eq!(server.hits(), 2);
+
+    BlockingJwksProvider::new()
+        .keys(&server.jku, KID)
+        .expect_err("jku is not allowed");
+    assert_eq!(server.hits(), 2);
+}
@@ -0,0 +1,112 @@
+//! PKCS#11 tests run against a SoftHSM token when `TL_SIGNING_PKCS11_MODULE` is set, e.g.
+//! ```sh
+//! mkdir -p /tmp/softhsm && echo "directories.tokendir = /tmp/softhsm" > /tmp/softhsm2.conf
+//! SOFTHSM2_CONF=/tmp/softhsm2.conf \
+//!   TL_SIGNING_PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so \
+//!   cargo test --features pkcs11 --test pkcs11
+//! ```
+//! The first slot's token is (re)initialized.
+#![cfg(feature = "pkcs11")]
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use cryptoki::{
+    context::{CInitializeArgs, CInitializeFlags, Pkcs11},
+    object::{Attribute, KeyType, ObjectClass},
+    session::{Session, UserType},
+    slot::Slot,
+    types::AuthPin,
+};
+use truelayer_signing::{Error, Method, Pkcs11Key, Pkcs11Signer};
+
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const PRIVATE_JWK: &[u8] = include_bytes!("../../test-resources/ec512-private-jwk.json");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+const LABEL: &str = "tl-signing-test";
+const ID: &[u8] = b"tl-1";
+const SO_PIN: &str = "abcdef654321";
+const USER_PIN: &str = "fedcba123456";
+
+/// Initialize the token & import the test-resources private key, returning
+/// a logged in user session that should be kept open.
+fn init_token(module: &str) -> (Pkcs11, Slot, Session) {
+    let pkcs11 = Pkcs11::new(module).expect("load pkcs11 module");
+    pkcs11
+        .initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))
+        .expect("initialize");
+    let slot = pkcs11.get_slots_with_token().unwrap().remove(0);
+    let so_pin = AuthPin::new(SO_PIN.into());
+    pkcs11
+        .init_token(slot, &so_pin, "tl-signing")
+        .expect("init_token");
+    {
+        let session = pkcs11.open_rw_session(slot).unwrap();
+        session.login(UserType::So, Some(&so_pin)).unwrap();
+        session.init_pin(&AuthPin::new(USER_PIN.into())).unwrap();
+    }
+
+    let session = pkcs11.open_rw_session(slot).unwrap();
+    session
+        .login(UserType::User, Some(&AuthPin::new(USER_PIN.into())))
+        .unwrap();
+
+    let jwk: serde_json::Value = serde_json::from_slice(PRIVATE_JWK).unwrap();
+    let d = URL_SAFE_NO_PAD.decode(jwk["d"].as_str().unwrap()).unwrap();
+    session
+        .create_object(&[
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC),
+            // P-521 named curve oid
+            Attribute::EcParams(vec![0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x23]),
+            Attribute::Value(d),
+            Attribute::Label(LABEL.into()),
+            Attribute::Id(ID.into()),
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Sign(true),
+        ])
+        .expect("import private key");
+    (pkcs11, slot, session)
+}
+
+fn sign_and_verify(signer: &Pkcs11Signer) {
+    let tl_signature = truelayer_signing::SignerBuilder::new()
+        .kid(KID)
+        .method(Method::Post)
+        .path("/payouts")
+        .header("Idempotency-Key", b"idemp-123")
+        .body(b"{}")
+        .build_custom_signer()
+        .sign_with_signer(signer)
+        .expect("sign");
+
+    truelayer_signing::verify_with_pem(PUBLIC_KEY)
+        .method(Method::Post)
+        .path("/payouts")
+        .header("Idempotency-Key", b"idemp-123")
+        .body(b"{}")
+        .build_verifier()
+        .verify(&tl_signature)
+        .expect("verify");
+}
+
+#[test]
+fn softhsm_sign() {
+    let Ok(module) = std::env::var("TL_SIGNING_PKCS11_MODULE") else {
+        eprintln!("TL_SIGNING_PKCS11_MODULE not set, skipping");
+        return;
+    };
+
+    let (pkcs11, slot, _session) = init_token(&module);
+    let open_session = || pkcs11.open_ro_session(slot).unwrap();
+
+    let signer = Pkcs11Signer::new(open_session(), Pkcs11Key::Label(LABEL)).expect("by label");
+    sign_and_verify(&signer);
+
+    let signer = Pkcs11Signer::new(open_session(), Pkcs11Key::Id(ID)).expect("by id");
+    sign_and_verify(&signer);
+
+    let error = Pkcs11Signer::new(open_session(), Pkcs11Key::Label("missing"))
+        .expect_err("missing key should fail");
+    assert!(matches!(error, Error::InvalidKey(_)), "{error:?}");
+}
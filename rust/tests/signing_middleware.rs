@@ -0,0 +1,118 @@
+#![cfg(feature = "reqwest-middleware")]
+
+use std::sync::{Arc, Mutex};
+
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use truelayer_signing::{Method, SigningKey, SigningMiddleware};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+const IDEMPOTENCY_KEY: &str = "idemp-2076717c-9005-4811-a321-9e0787fa0382";
+
+/// Terminal middleware capturing the request instead of sending it.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Option<reqwest::Request>>>);
+
+impl Capture {
+    fn take(&self) -> reqwest::Request {
+        self.0.lock().unwrap().take().expect("captured request")
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for Capture {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        _: &mut http::Extensions,
+        _: Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        *self.0.lock().unwrap() = Some(request);
+        Ok(http::Response::new("").into())
+    }
+}
+
+fn client(middleware: SigningMiddleware) -> (ClientWithMiddleware, Capture) {
+    let capture = Capture::default();
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(middleware)
+        .with(capture.clone())
+        .build();
+    (client, capture)
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_pem(KID, PRIVATE_KEY).expect("signing key")
+}
+
+#[tokio::test]
+async fn signs_post() {
+    let (client, capture) = client(SigningMiddleware::new(signing_key()));
+    let body = br#"{"currency":"GBP","max_amount_in_minor":5000000}"#;
+
+    client
+        .post("https://api.truelayer.com/merchant_accounts/a61acaef/sweeping?q=1")
+        .header("Idempotency-Key", IDEMPOTENCY_KEY)
+        .header("X-Whatever", "aoitbeh")
+        .body(body.to_vec())
+        .send()
+        .await
+        .expect("send");
+
+    let request = capture.take();
+    let tl_signature = request.headers()["Tl-Signature"].to_str().unwrap();
+    let jws_header = truelayer_signing::extract_jws_header(tl_signature).unwrap();
+    assert_eq!(jws_header.tl_headers.as_deref(), Some("Idempotency-Key"));
+
+    truelayer_signing::verify_with_pem(PUBLIC_KEY)
+        .method(Method::Post)
+        .path("/merchant_accounts/a61acaef/sweeping")
+        .header("Idempotency-Key", IDEMPOTENCY_KEY.as_bytes())
+        .body(body)
+        .build_verifier()
+        .verify(tl_signature)
+        .expect("verify");
+}
+
+#[tokio::test]
+async fn signs_configured_headers() {
+    let middleware =
+        SigningMiddleware::new(signing_key()).signed_headers(["Idempotency-Key", "X-Custom"]);
+    let (client, capture) = client(middleware);
+
+    // X-Custom absent, so not signed
+    client
+        .delete("https://api.truelayer.com/mandates/123")
+        .header("Idempotency-Key", IDEMPOTENCY_KEY)
+        .send()
+        .await
+        .expect("send");
+
+    let request = capture.take();
+    let tl_signature = request.headers()["Tl-Signature"].to_str().unwrap();
+    let jws_header = truelayer_signing::extract_jws_header(tl_signature).unwrap();
+    assert_eq!(jws_header.tl_headers.as_deref(), Some("Idempotency-Key"));
+
+    truelayer_signing::verify_with_pem(PUBLIC_KEY)
+        .method(Method::Delete)
+        .path("/mandates/123")
+        .header("Idempotency-Key", IDEMPOTENCY_KEY.as_bytes())
+        .body(b"")
+        .build_verifier()
+        .verify(tl_signature)
+        .expect("verify");
+}
+
+#[tokio::test]
+async fn get_is_not_signed() {
+    let (client, capture) = client(SigningMiddleware::new(signing_key()));
+
+    client
+        .get("https://api.truelayer.com/payouts/123")
+        .send()
+        .await
+        .expect("send");
+
+    assert!(capture.take().headers().get("Tl-Signature").is_none());
+}
@@ -0,0 +1,289 @@
+#![cfg(feature = "tower")]
+
+use std::{convert::Infallible, error::Error, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use http::{Method, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full};
+use tower::{service_fn, Layer, ServiceExt};
+use truelayer_signing::{
+    AsyncReplayGuard, InMemoryReplayGuard, ReplayKey, SigningKey, TrailingSlash, VerifiedSignature,
+    VerifyConfig, VerifySignatureLayer, VerifyingKeySet,
+};
+
+const PRIVATE_KEY: &[u8] = include_bytes!("../../test-resources/ec512-private.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("../../test-resources/ec512-public.pem");
+const KID: &str = "45fc75cf-5649-4134-84b3-192c2c78e990";
+type BoxError = Box<dyn Error + Send + Sync>;
+
+const BODY: &[u8] = br#"{"currency":"GBP","max_amount_in_minor":5000000}"#;
+
+fn config() -> VerifyConfig {
+    let keys = VerifyingKeySet::new()
+        .with_pem(KID, PUBLIC_KEY)
+        .expect("verifying key");
+    VerifyConfig::new(keys)
+}
+
+fn layer() -> VerifySignatureLayer {
+    VerifySignatureLayer::new(config())
+}
+
+fn signed_request(signed_headers: &[&str]) -> Request<Full<Bytes>> {
+    let signing_key = SigningKey::from_pem(KID, PRIVATE_KEY).expect("signing key");
+    let mut request = Request::post("/merchant_accounts/a61acaef/sweeping")
+        .header(
+            "Idempotency-Key",
+            "idemp-2076717c-9005-4811-a321-9e0787fa0382",
+        )
+        .body(BODY.to_vec())
+        .unwrap();
+    truelayer_signing::sign_request(&signing_key, &mut request, signed_headers)
+        .expect("sign_request");
+    request.map(|body| Full::new(body.into()))
+}
+
+/// Call an echo service wrapped by `layer`, returning the status & response body.
+async fn call<B>(layer: VerifySignatureLayer, request: Request<B>) -> (StatusCode, Bytes)
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let echo = service_fn(|request: Request<Full<Bytes>>| async move {
+        let body = request.into_body().collect().await?.to_bytes();
+        Ok::<_, Infallible>(Response::new(Full::new(body)))
+    });
+
+    let response = layer.layer(echo).oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body)
+}
+
+#[tokio::test]
+async fn verified_request_passes_body() {
+    let (status, body) = call(layer(), signed_request(&["Idempotency-Key"])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, BODY);
+}
+
+#[tokio::test]
+async fn modified_body_rejected() {
+    let request = signed_request(&[]).map(|_| Full::new(Bytes::from_static(b"{}")));
+
+    let (status, body) = call(layer(), request).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "invalid_signature");
+    assert_eq!(body["reason"], "signature verification failed");
+}
+
+#[tokio::test]
+async fn missing_signature_rejected() {
+    let request = Request::post("/payouts").body(Full::new(Bytes::from_static(BODY)));
+
+    let (status, body) = call(layer(), request.unwrap()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "malformed_signature");
+}
+
+/// The rejection `reason` includes the cause of the failure.
+#[tokio::test]
+async fn rejection_reason_includes_source() {
+    let request = Request::post("/payouts")
+        .header("Tl-Signature", "not-base64!..c2ln")
+        .body(Full::new(Bytes::from_static(BODY)))
+        .unwrap();
+
+    let (status, body) = call(layer(), request).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "malformed_signature");
+    let reason = body["reason"].as_str().unwrap();
+    assert!(
+        reason.starts_with("malformed signature: ") && reason.matches(": ").count() >= 2,
+        "{reason}"
+    );
+}
+
+#[tokio::test]
+async fn unknown_kid_rejected() {
+    let keys = VerifyingKeySet::new()
+        .with_pem("another-kid", PUBLIC_KEY)
+        .unwrap();
+
+    let (status, body) = call(
+        VerifySignatureLayer::new(VerifyConfig::new(keys)),
+        signed_request(&[]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "unknown_kid");
+}
+
+/// Jwks fetch failures are a server-side problem, not an invalid request.
+#[cfg(feature = "jwks-client")]
+#[tokio::test]
+async fn jwks_fetch_failure_bad_gateway() {
+    // nothing listens on port 1
+    let jku = "http://127.0.0.1:1/.well-known/jwks";
+    let provider = truelayer_signing::JwksProvider::new().allowed_jkus([jku]);
+    let tl_signature = truelayer_signing::sign_with_pem(KID, PRIVATE_KEY)
+        .method(truelayer_signing::Method::Post)
+        .path("/payouts")
+        .body(BODY)
+        .jku(jku)
+        .build_signer()
+        .sign()
+        .unwrap();
+    let request = Request::post("/payouts")
+        .header("Tl-Signature", tl_signature)
+        .body(Full::new(Bytes::from_static(BODY)))
+        .unwrap();
+
+    let (status, body) = call(
+        VerifySignatureLayer::new(VerifyConfig::jwks(provider)),
+        request,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "jwks_fetch_failed");
+}
+
+#[tokio::test]
+async fn replay_guard() {
+    struct Unavailable;
+    impl AsyncReplayGuard for Unavailable {
+        async fn insert(&self, _: &[u8], _: Duration) -> Result<bool, BoxError> {
+            Err("store unavailable".into())
+        }
+    }
+
+    let replay_guard = Arc::new(InMemoryReplayGuard::new());
+    let layer = VerifySignatureLayer::new(config().replay_guard(
+        replay_guard,
+        ReplayKey::Signature,
+        Duration::from_secs(60),
+    ));
+    let (status, _) = call(layer.clone(), signed_request(&["Idempotency-Key"])).await;
+    assert_eq!(status, StatusCode::OK);
+    // the same signed payload is a replay
+    let (status, body) = call(layer, signed_request(&["Idempotency-Key"])).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "replayed");
+
+    let layer = VerifySignatureLayer::new(config().replay_guard(
+        Arc::new(Unavailable),
+        ReplayKey::Signature,
+        Duration::from_secs(60),
+    ));
+    let (status, body) = call(layer, signed_request(&["Idempotency-Key"])).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "replay_guard_failed");
+}
+
+#[tokio::test]
+async fn required_header() {
+    let layer = VerifySignatureLayer::new(config().require_header("Idempotency-Key"));
+
+    let (status, body) = call(layer.clone(), signed_request(&[])).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "missing_required_header");
+    assert_eq!(
+        body["reason"],
+        "signature is missing required header Idempotency-Key"
+    );
+
+    let (status, _) = call(layer, signed_request(&["Idempotency-Key"])).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn trailing_slash() {
+    let trailing_slash_request = || {
+        let mut request = signed_request(&[]);
+        *request.uri_mut() = "/merchant_accounts/a61acaef/sweeping/".parse().unwrap();
+        request
+    };
+
+    // tolerated, reporting the signed path
+    let service = layer().layer(service_fn(|request: Request<Full<Bytes>>| async move {
+        let verified = request.extensions().get::<VerifiedSignature>().cloned();
+        let signed_path = verified.and_then(|v| v.signed_path).unwrap_or_default();
+        Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(signed_path))))
+    }));
+    let response = service.oneshot(trailing_slash_request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "/merchant_accounts/a61acaef/sweeping");
+
+    let layer = VerifySignatureLayer::new(config().trailing_slash(TrailingSlash::Strict));
+    let (status, _) = call(layer, trailing_slash_request()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+/// Request bodies are buffered & passed on as `Full<Bytes>`, so any body type is accepted,
+/// e.g. hyper `Incoming`.
+#[tokio::test]
+async fn boxed_body() {
+    let request = signed_request(&["Idempotency-Key"]).map(|body| body.boxed_unsync());
+    let (status, body) = call(layer(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, BODY);
+}
+
+#[tokio::test]
+async fn unverified_methods() {
+    let options = || {
+        let mut request = signed_request(&[]);
+        *request.method_mut() = Method::OPTIONS;
+        request
+    };
+
+    let (status, body) = call(layer(), options()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "unsupported_method");
+
+    let service = layer()
+        .unverified_methods([Method::OPTIONS, Method::HEAD])
+        .layer(service_fn(|request: Request<Full<Bytes>>| async move {
+            assert!(request.extensions().get::<VerifiedSignature>().is_none());
+            Ok::<_, Infallible>(Response::new(Full::new(Bytes::new())))
+        }));
+    let response = service.oneshot(options()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_limit() {
+    let (status, _) = call(layer().body_limit(8), signed_request(&[])).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn verified_signature_extension() {
+    let service = layer().layer(service_fn(|request: Request<Full<Bytes>>| async move {
+        let verified = request.extensions().get::<VerifiedSignature>().cloned();
+        let verified = verified.expect("VerifiedSignature extension");
+        assert_eq!(verified.signed_headers, ["Idempotency-Key"]);
+        Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(verified.kid))))
+    }));
+
+    let response = service
+        .oneshot(signed_request(&["Idempotency-Key"]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, KID);
+}